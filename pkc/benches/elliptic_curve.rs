@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use num_bigint::{BigInt, Sign};
+use pkc::ecdsa::SigningKey;
+use pkc::elliptic_curve_primitives::NamedCurve;
+use pkc::rng;
+
+//scalar multiplication k*G (constant-time ladder and variable-time double and add)
+//and ECDSA sign/verify on P-256
+fn bench_p256(c: &mut Criterion) {
+    let mut rng = rand::rng();
+    let mut group = c.benchmark_group("p256");
+
+    let domain = NamedCurve::P256.domain_parameters();
+    let k = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(256, &mut rng));
+
+    group.bench_function("scalar_multiplication", |b| {
+        b.iter(|| domain.curve().point_n_addition(domain.generator().clone(), k.clone()).unwrap())
+    });
+    group.bench_function("scalar_multiplication_vartime", |b| {
+        b.iter(|| domain.curve().point_n_addition_vartime(domain.generator().clone(), k.clone()))
+    });
+
+    let signing_key = SigningKey::random(domain.clone(), &mut rng);
+    let signature = signing_key.sign(b"benchmark message");
+
+    group.bench_function("ecdsa_sign", |b| {
+        b.iter(|| signing_key.sign(b"benchmark message"))
+    });
+    group.bench_function("ecdsa_verify", |b| {
+        b.iter(|| signing_key.verifying_key().verify(b"benchmark message", &signature))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_p256);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::{BigInt, Sign};
+use pkc::number_theory_primitives as helper;
+use pkc::rng;
+use pkc::rsa_cryptosystem::RsaPrivateKey;
+
+//compares costly_modular_exponentiation, a one-off montgomery_modular_exponentiation
+//and a reused MontgomeryContext on random odd moduli of common key sizes
+fn bench_modular_exponentiation(c: &mut Criterion) {
+    let mut rng = rand::rng();
+    let mut group = c.benchmark_group("modular_exponentiation");
+
+    for bits in [512u64, 1024, 2048] {
+        let mut n = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits, &mut rng));
+        n.set_bit(0, true);
+        n.set_bit(bits - 1, true);
+        let a = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits - 1, &mut rng));
+        let e = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits, &mut rng));
+
+        let context = helper::MontgomeryContext::new(&n).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("costly", bits), &bits, |b, _| {
+            b.iter(|| helper::costly_modular_exponentiation(a.clone(), e.clone(), n.clone()))
+        });
+        group.bench_with_input(BenchmarkId::new("montgomery", bits), &bits, |b, _| {
+            b.iter(|| helper::montgomery_modular_exponentiation(a.clone(), e.clone(), n.clone()))
+        });
+        group.bench_with_input(BenchmarkId::new("montgomery_context", bits), &bits, |b, _| {
+            b.iter(|| context.modpow(&a, &e))
+        });
+    }
+
+    group.finish();
+}
+
+//RSA private key operation as a single exponentiation with d mod n versus the CRT
+//path taken by RsaPrivateKey::decrypt (which also re-encrypts to detect faults)
+fn bench_rsa_private_operation(c: &mut Criterion) {
+    let mut rng = rand::rng();
+    let mut group = c.benchmark_group("rsa_private_operation");
+
+    let private_key = RsaPrivateKey::generate(2048, &mut rng).unwrap();
+    let n = private_key.public_key().n().clone();
+    let ciphertext = BigInt::from_biguint(Sign::Plus, rng::generate_random_below(n.magnitude(), &mut rng));
+
+    group.bench_function("d_mod_n", |b| {
+        b.iter(|| helper::costly_modular_exponentiation(ciphertext.clone(), private_key.d().clone(), n.clone()))
+    });
+    group.bench_function("crt", |b| {
+        b.iter(|| private_key.decrypt(&ciphertext).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_modular_exponentiation, bench_rsa_private_operation);
+criterion_main!(benches);
@@ -1,6 +1,5 @@
-use num_bigint::BigUint;
-use hex;
-use crate::rng;
+use num_bigint::{BigInt, BigUint};
+use crate::number_theory_primitives::montgomery_modular_exponentiation;
 
 /// Diffie-Hellman parameters
 ///     - generator = generator g of group Z_p, p a large prime
@@ -12,6 +11,7 @@ pub struct DiffieHellmanPublic {
 
 
 pub struct DiffieHellmanPrivate {
+    #[allow(dead_code)]
     x: BigUint
 }
 
@@ -24,7 +24,7 @@ pub struct DiffieHellmanPrivate {
 ///output:
 ///     - party's public parameter
 pub fn compute_public_key(g: &BigUint, exponent: &BigUint, modulus:&BigUint) -> BigUint {
-    g.modpow(exponent, modulus)
+    modular_exponentiation(g, exponent, modulus)
 }
 
 ///computes A^b mod p and similarly B^a mod p
@@ -38,7 +38,19 @@ pub fn compute_public_key(g: &BigUint, exponent: &BigUint, modulus:&BigUint) ->
 /// output:
 ///     - A^b mod p
 pub fn compute_shared_key(party_1_priv:BigUint, party_2_pub:&BigUint, modulus:&BigUint) -> BigUint {
-    party_2_pub.modpow(&party_1_priv, modulus)
+    modular_exponentiation(party_2_pub, &party_1_priv, modulus)
+}
+
+//the exponent is a private key, so this goes through the constant time montgomery
+//exponentiation rather than BigUint::modpow
+fn modular_exponentiation(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    let result: BigInt = montgomery_modular_exponentiation(
+        BigInt::from(base.clone()),
+        BigInt::from(exponent.clone()),
+        BigInt::from(modulus.clone())
+    );
+
+    result.magnitude().clone()
 }
 
 
@@ -47,6 +59,7 @@ pub fn compute_shared_key(party_1_priv:BigUint, party_2_pub:&BigUint, modulus:&B
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rng;
 
     #[test]
     fn test_small_uints() {
@@ -1,274 +1,1235 @@
-use std::fmt::format;
-use std::ops::{Add, Div, Rem};
-use num_bigint::BigInt;
-use num_bigint::BigUint;
-use num_traits::One;
-use rand::Rng;
-use crate::number_theory_primitives as helper;
-use sha2::{Sha256, Digest};
+use std::fmt;
+use std::ops::Rem;
+use num_bigint::{BigInt, Sign};
+use rand::CryptoRng;
+use crate::number_theory_primitives::{is_prime, montgomery_modular_exponentiation, MontgomeryContext};
+use crate::rfc6979;
+use crate::rng;
+use sha2::{Digest, Sha224, Sha256};
+use sha2::digest::core_api::BlockSizeUser;
 
 
-fn main(message:BigInt) {
-    //implementation of DSA for small primes p and q
-    //
-    //main() generates a digital signature for integer message and
-    //validates the signature
+//digital signature algorithm (FIPS 186-4 section 4)
+//
+//signatures live in the subgroup of order q of Z_p^*, generated by g. the private
+//key is x in [1, q-1], the public key y = g^x mod p, and a signature over message
+//m is a pair (r, s) of integers mod q
+//
+//(L, N) is restricted to the pairs of FIPS 186-4 section 4.2 that are still approved
+//for signing, with 1024/160 parameters accepted only to verify legacy signatures
 
-    // since 5|30
-    let prime_pair = DsaPrimePair::new_small(p=31, q=5)
+//miller-rabin rounds for p and q, enough for every (L, N) in FIPS 186-4 table C.1
+const PRIME_TEST_ROUNDS: usize = 64;
 
-    //set private key d = 3
-    let k_priv = DsaPrivateKey::new(d=3, q=prime_pair.q)
+//bit length of SHA-256, the hash used to generate parameters from a seed
+const OUTLEN: u64 = 256;
 
-    //generate public parameters alpha and b and return pub/priv key pair
-    let dsa_key_pair = DsaKeyPair::new(primes=&prime_pair, k_priv=&k_priv)
 
-    //generate signature (r,s) by setting ephemeral_key equal to 4 only for this message
-    let dsa_signature = dsa_key_pair.generate_signature(ephemeral_key=4 , primes=&prime_pair, m=&message)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DsaError {
+    InvalidParameters,
+    InvalidPrivateKey,
+    InvalidPublicKey,
+    MissingValidationParameters,
+    ParameterValidation,
+    LegacyParameters,
+    InvalidDigestLength,
+}
+
+impl fmt::Display for DsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DsaError::InvalidParameters => write!(f, "domain parameters must have an approved (L, N), q | p - 1 and g of order q"),
+            DsaError::InvalidPrivateKey => write!(f, "private key must satisfy 1 <= x <= q - 1"),
+            DsaError::InvalidPublicKey => write!(f, "public key must satisfy 1 < y < p and y^q = 1 mod p"),
+            DsaError::MissingValidationParameters => write!(f, "parameters carry no seed to validate against"),
+            DsaError::ParameterValidation => write!(f, "p, q or g does not match the one derived from the seed"),
+            DsaError::LegacyParameters => write!(f, "1024-bit parameters may only verify existing signatures"),
+            DsaError::InvalidDigestLength => write!(f, "digest length does not match the hash function"),
+        }
+    }
+}
+
+impl std::error::Error for DsaError {}
+
+
+///bit lengths (L, N) of p and q, FIPS 186-4 section 4.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsaParameterSize {
+    L1024N160,
+    L2048N224,
+    L2048N256,
+    L3072N256,
+}
+
+impl DsaParameterSize {
+    pub const ALL: [DsaParameterSize; 4] = [
+        DsaParameterSize::L1024N160,
+        DsaParameterSize::L2048N224,
+        DsaParameterSize::L2048N256,
+        DsaParameterSize::L3072N256,
+    ];
+
+    ///L, the bit length of p
+    pub fn l(&self) -> u64 {
+        match self {
+            DsaParameterSize::L1024N160 => 1024,
+            DsaParameterSize::L2048N224 | DsaParameterSize::L2048N256 => 2048,
+            DsaParameterSize::L3072N256 => 3072,
+        }
+    }
+
+    ///N, the bit length of q and of the truncated message hash
+    pub fn n(&self) -> u64 {
+        match self {
+            DsaParameterSize::L1024N160 => 160,
+            DsaParameterSize::L2048N224 => 224,
+            DsaParameterSize::L2048N256 | DsaParameterSize::L3072N256 => 256,
+        }
+    }
 
-    //validate signature
-    let result = dsa_signature.verify_signature(m=&message, primes=&prime_pair, key_pair=&dsa_key_pair)
+    pub fn from_lengths(l: u64, n: u64) -> Option<DsaParameterSize> {
+        DsaParameterSize::ALL.into_iter().find(|size| size.l() == l && size.n() == n)
+    }
 
-    if result==true{
-        println!("Signature is valid")
-    } else {
-        println!("Signaure is invalid")
+    ///1024/160 gives under 112 bits of security and is disallowed for signing by
+    ///SP 800-131A, so it is only kept to verify old signatures
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, DsaParameterSize::L1024N160)
     }
 }
 
-// public primes
-struct DsaPrimePair {
+
+///DSA domain parameters
+///
+/// fields:
+///     - p = prime modulus
+///     - q = prime divisor of p - 1, the order of the signing subgroup
+///     - g = generator of the subgroup of order q in Z_p^*
+///     - validation = seed, counter and index the parameters were derived from, if known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaParameters {
     p: BigInt,
-    q: BigInt
+    q: BigInt,
+    g: BigInt,
+    validation: Option<DsaValidationParameters>
 }
 
+///record of a FIPS 186-4 parameter generation, kept so anyone can re-derive and check
+///the parameters
+///
+/// fields:
+///     - seed = domain_parameter_seed of A.1.1.2, at least N bits
+///     - counter = iteration of A.1.1.2 at which p was found, 0 <= counter <= 4L - 1
+///     - index = 8-bit index of the verifiable generator g (A.2.3)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaValidationParameters {
+    seed: Vec<u8>,
+    counter: u32,
+    index: u8
+}
 
-impl DsaPrimePair {
+impl DsaValidationParameters {
+    pub fn new(seed: Vec<u8>, counter: u32, index: u8) -> Self {
+        Self { seed, counter, index }
+    }
 
-    fn new(p: BigInt, q:BigInt) -> Self {
-        //new() static method checks:
-        // 1. proposed primes are sufficiently large (1023 < p < 1024 bits and 159 < q < 160 bits)
-        // 2. q | euler_totient(p)
-        // 
-        // returns DsaPrimePair object on success, otherwise program panics
+    pub fn seed(&self) -> &[u8] {
+        &self.seed
+    }
 
-        let p_min: BigInt = BigInt::from(2).pow(1023);
-        let p_max: BigInt = BigInt::from(2).pow(1024);
+    pub fn counter(&self) -> u32 {
+        self.counter
+    }
 
-        let q_min: BigInt = BigInt::from(2).pow(159);
-        let q_max: BigInt = BigInt::from(2).pow(160);
-        
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+}
 
-        if p_min > p || p > p_max {
-            panic!("prime p not within expected range")
-        };
+impl DsaParameters {
+    ///new() checks the structure of the parameters:
+    /// 1. (bitlen(p), bitlen(q)) is one of the DsaParameterSize pairs
+    /// 2. p and q are odd with q | p - 1
+    /// 3. 1 < g < p and g^q = 1 mod p, so g generates the subgroup of order q
+    /// 4. p and q are probable primes, so every 0 < s < q has an inverse mod q
+    pub fn new(p: BigInt, q: BigInt, g: BigInt) -> Result<Self, DsaError> {
+        let one = BigInt::from(1);
 
-        if q_min > q || q > q_max {
-            panic!("prime q not within expected range")
-        };
+        if DsaParameterSize::from_lengths(p.bits(), q.bits()).is_none() {
+            return Err(DsaError::InvalidParameters)
+        }
+        if q <= one || p <= q || !p.bit(0) || !q.bit(0) {
+            return Err(DsaError::InvalidParameters)
+        }
+        if (&p - &one).rem(&q) != BigInt::ZERO {
+            return Err(DsaError::InvalidParameters)
+        }
+        if g <= one || g >= p || g.modpow(&q, &p) != one {
+            return Err(DsaError::InvalidParameters)
+        }
+        if !is_prime(&q, PRIME_TEST_ROUNDS) || !is_prime(&p, PRIME_TEST_ROUNDS) {
+            return Err(DsaError::InvalidParameters)
+        }
 
-        let p_minus_one = &p - BigInt::from(1);
+        Ok(Self { p, q, g, validation: None })
+    }
+
+    ///new_verifiable() takes parameters received together with the seed, counter and
+    ///index they were generated from. call validate() to check them
+    pub fn new_verifiable(p: BigInt, q: BigInt, g: BigInt, validation: DsaValidationParameters) -> Result<Self, DsaError> {
+        let parameters = Self::new(p, q, g)?;
+
+        Ok(Self { validation: Some(validation), ..parameters })
+    }
 
-        if !helper::does_divide(&q, &p_minus_one ) {
-            panic!("primes p,q not configured properly: q does not divide p-1")
+    ///generate() derives fresh parameters of the given size with SHA-256:
+    /// 1. draw an N-bit seed and derive probable primes p and q from it (FIPS 186-4
+    ///    A.1.1.2), starting over with a new seed until both are found
+    /// 2. derive the verifiable generator g with index 1 (A.2.3)
+    ///
+    ///the seed, counter and index are recorded so that validate() can repeat the work.
+    ///legacy 1024/160 parameters are not generated
+    pub fn generate<R: CryptoRng + ?Sized>(size: DsaParameterSize, rng: &mut R) -> Result<Self, DsaError> {
+        if size.is_legacy() {
+            return Err(DsaError::LegacyParameters)
         }
 
-        Self { p, q }
+        let (l, n) = (size.l(), size.n());
+        let index = 1;
+        let mut seed = vec![0u8; n.div_ceil(8) as usize];
+
+        loop {
+            rng.fill_bytes(&mut seed);
+
+            let q = generate_q(n, &seed);
+            if !is_prime(&q, PRIME_TEST_ROUNDS) {
+                continue
+            }
+
+            if let Some((p, counter)) = generate_p(l, &q, &seed, 4*l as u32 - 1) {
+                let g = generate_g(&p, &q, &seed, index).unwrap();
+                let validation = DsaValidationParameters { seed, counter, index };
+
+                return Ok(Self { p, q, g, validation: Some(validation) })
+            }
+        }
     }
 
-    fn new_small(p: BigInt, q:BigInt) -> Self {
-        //new_small() is a static method that checks:
-        // 1. q | phi(p)
-        // 
-        //returns DsaPrimePair object on success, otherwise program panics
-        //
-        //used only in demonstration with small primes
+    ///validate() re-derives the parameters from the recorded seed (FIPS 186-4 A.1.1.3
+    ///and A.2.4):
+    /// 1. the seed has at least N bits and counter <= 4L - 1
+    /// 2. q is the prime derived from the seed
+    /// 3. p is the first prime found by A.1.1.2, at exactly the recorded counter
+    /// 4. g is the generator derived from the seed and index, with g^q = 1 mod p
+    pub fn validate(&self) -> Result<(), DsaError> {
+        let DsaValidationParameters { seed, counter, index } = self.validation.as_ref()
+            .ok_or(DsaError::MissingValidationParameters)?;
+        let (l, n) = (self.p.bits(), self.q.bits());
+
+        if (seed.len() as u64) * 8 < n || *counter as u64 > 4*l - 1 {
+            return Err(DsaError::ParameterValidation)
+        }
+
+        if generate_q(n, seed) != self.q || !is_prime(&self.q, PRIME_TEST_ROUNDS) {
+            return Err(DsaError::ParameterValidation)
+        }
 
-        let p_minus_one = &p - BigInt::from(1);
+        match generate_p(l, &self.q, seed, *counter) {
+            Some((p, found)) if p == self.p && found == *counter => {},
+            _ => return Err(DsaError::ParameterValidation)
+        }
 
-        if !helper::does_divide(&q, &p_minus_one ) {
-            panic!("primes p,q not configured properly: q does not divide p-1")
+        let g = generate_g(&self.p, &self.q, seed, *index);
+        if g.as_ref() != Some(&self.g) || self.g.modpow(&self.q, &self.p) != BigInt::from(1) {
+            return Err(DsaError::ParameterValidation)
         }
 
-        Self { p, q }
+        Ok(())
     }
-}
 
-//construct public key object containing
-//1. primes p,q
-//2. alpha
-//3. B
-//where alpha and B are parameters computed during key generation
-struct DsaPublicKey {
-    p: BigInt,
-    q: BigInt,
-    alpha: BigInt,
-    b: BigInt
+    pub fn size(&self) -> DsaParameterSize {
+        DsaParameterSize::from_lengths(self.p.bits(), self.q.bits()).unwrap()
+    }
+
+    pub fn validation_parameters(&self) -> Option<&DsaValidationParameters> {
+        self.validation.as_ref()
+    }
+
+    pub fn p(&self) -> &BigInt {
+        &self.p
+    }
+
+    pub fn q(&self) -> &BigInt {
+        &self.q
+    }
+
+    pub fn g(&self) -> &BigInt {
+        &self.g
+    }
 }
 
-//object to hold private key
-struct DsaPrivatekey {
-    d: BigInt
+
+///DSA public key y = g^x mod p together with its domain parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaPublicKey {
+    parameters: DsaParameters,
+    y: BigInt
 }
 
-impl DsaPrivatekey {
-    fn new(d: BigInt, q: BigInt) -> Self {
-        //new() method validates size of d
-        //on success returns back DsaPrivateKey object
-        if BigInt::from(1) > d || d > q - BigInt::from(1) {
-            panic!("private key d needs to satisfy: 1 <= d <= (q - 1)")
-        };
+///DSA key pair
+///
+/// fields:
+///     - x = private key, 1 <= x <= q - 1
+///     - public_key = domain parameters and y = g^x mod p
+#[derive(Clone, PartialEq, Eq)]
+pub struct DsaKeyPair {
+    x: BigInt,
+    public_key: DsaPublicKey
+}
 
-        Self { d }
-    }
+///DSA signature (r, s)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaSignature {
+    r: BigInt,
+    s: BigInt
 }
 
-//abstraction of previous key generation objects
-struct DsaKeyPair {
-    k_pub: DsaPublicKey,
-    k_priv: DsaPrivatekey
+//only the public key is printed so x can't leak through logs
+impl fmt::Debug for DsaKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DsaKeyPair")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
 }
 
 impl DsaKeyPair {
-    fn new(primes: DsaPrimePair, k_priv: DsaPrivatekey) -> Self {
-        //new() method takes public parameters p,q, private d and
-        //initializes g=2 for a generator of Z_q. 
-        //
-        //to compute alpha we loop through values of g until we find
-        //an alpha such that alpha=g^e != 1 mod p.
-        //
-        //to compute B raise alpha to private key d mod p.
-        //
-        //finally return DsaKeyPair as an abstraction of public and private key interfaces
-        let p = primes.p;
-        let q = primes.q;
-        let d = k_priv.d;
-
-        let exponent = (&p - BigInt::from(1)).div(&q);
-        let mut g = BigInt::from(2);
-        let mut alpha = BigInt::ZERO;
-
-        while g < p {
-            alpha = g.modpow(&exponent, &p);
-            if alpha != BigInt::from(1) {
-                break
+    ///generate() draws x uniformly from [1, q-1] (FIPS 186-4 appendix B.1.2)
+    pub fn generate<R: CryptoRng + ?Sized>(parameters: &DsaParameters, rng: &mut R) -> Result<Self, DsaError> {
+        let (_, bound) = (&parameters.q - BigInt::from(1)).into_parts();
+        let x = BigInt::from(rng::generate_random_below(&bound, rng)) + BigInt::from(1);
+
+        Self::from_private_key(parameters.clone(), x)
+    }
+
+    ///from_private_key() checks 1 <= x <= q - 1 and computes y = g^x mod p. signing
+    ///keys can't be made on legacy 1024/160 parameters
+    pub fn from_private_key(parameters: DsaParameters, x: BigInt) -> Result<Self, DsaError> {
+        if parameters.size().is_legacy() {
+            return Err(DsaError::LegacyParameters)
+        }
+        if x < BigInt::from(1) || x >= parameters.q {
+            return Err(DsaError::InvalidPrivateKey)
+        }
+
+        //x is secret, so this takes the constant time exponentiation over the N bits of q
+        let context = MontgomeryContext::new(&parameters.p).expect("p is an odd prime");
+        let y = context.modpow_bounded(&parameters.g, &x, parameters.q.bits());
+        let public_key = DsaPublicKey { parameters, y };
+
+        Ok(Self { x, public_key })
+    }
+
+    pub fn public_key(&self) -> &DsaPublicKey {
+        &self.public_key
+    }
+
+    ///sign() hashes the message with the default hash for the parameter size and
+    ///signs it with the deterministic per-message secret k of RFC 6979, so the same key
+    ///and message always give the same signature. the hash is SHA-224 for N = 224 and
+    ///SHA-256 otherwise
+    pub fn sign(&self, message: &[u8]) -> DsaSignature {
+        match DefaultHash::for_size(self.public_key.parameters.size()) {
+            DefaultHash::Sha224 => self.sign_digest(Sha224::new_with_prefix(message)),
+            DefaultHash::Sha256 => self.sign_digest(Sha256::new_with_prefix(message)),
+        }
+    }
+
+    ///sign_digest() signs a hasher the caller has fed with update(), so large messages
+    ///can be streamed instead of held in memory. D can be any hash (SHA-384, SHA-512/256,
+    ///SHA3-256, ...) and is also the HMAC hash of the RFC 6979 derivation
+    pub fn sign_digest<D: Digest + BlockSizeUser>(&self, digest: D) -> DsaSignature {
+        self.sign_with_additional_data::<D>(&digest.finalize(), &[])
+    }
+
+    ///sign_prehashed() signs a digest the caller has already computed with D
+    pub fn sign_prehashed<D: Digest + BlockSizeUser>(&self, digest: &[u8]) -> Result<DsaSignature, DsaError> {
+        if digest.len() != <D as Digest>::output_size() {
+            return Err(DsaError::InvalidDigestLength)
+        }
+
+        Ok(self.sign_with_additional_data::<D>(digest, &[]))
+    }
+
+    ///sign_hedged() mixes fresh randomness from rng into the RFC 6979 derivation
+    ///(section 3.6), so signatures are randomised but k stays secret even if rng is weak
+    pub fn sign_hedged<R: CryptoRng + ?Sized>(&self, message: &[u8], rng: &mut R) -> DsaSignature {
+        match DefaultHash::for_size(self.public_key.parameters.size()) {
+            DefaultHash::Sha224 => self.sign_digest_hedged(Sha224::new_with_prefix(message), rng),
+            DefaultHash::Sha256 => self.sign_digest_hedged(Sha256::new_with_prefix(message), rng),
+        }
+    }
+
+    ///sign_digest_hedged() is sign_hedged() over a hasher the caller has fed with update()
+    pub fn sign_digest_hedged<D: Digest + BlockSizeUser, R: CryptoRng + ?Sized>(&self, digest: D, rng: &mut R) -> DsaSignature {
+        let mut entropy = vec![0u8; self.public_key.parameters.q.bits().div_ceil(8) as usize];
+        rng.fill_bytes(&mut entropy);
+
+        self.sign_with_additional_data::<D>(&digest.finalize(), &entropy)
+    }
+
+    //1. z = leftmost min(N, outlen) bits of the digest H(m), so longer hashes are truncated
+    //2. draw k from the RFC 6979 HMAC-DRBG seeded with x, H(m) and any additional data
+    //3. sign z with k, drawing the next k if r or s comes out zero
+    fn sign_with_additional_data<D: Digest + BlockSizeUser>(&self, digest: &[u8], additional_data: &[u8]) -> DsaSignature {
+        let q = &self.public_key.parameters.q;
+
+        let z = rfc6979::bits2int(digest, q.bits());
+        let mut nonces = rfc6979::NonceGenerator::<D>::new(q, &self.x, digest, additional_data);
+
+        loop {
+            let k = nonces.next_nonce();
+
+            if let Some(signature) = self.sign_with_nonce(&z, &k) {
+                return signature
             }
-            g+=BigInt::from(1);
-        };
+        }
+    }
+
+    //signs the truncated digest z with nonce 1 <= k <= q - 1:
+    // 1. r = (g^k mod p) mod q
+    // 2. s = k^-1 (z + x*r) mod q, with k^-1 = k^(q-2) mod q as q is prime
+    //k is secret, so both exponentiations take the constant time montgomery path, with
+    //g^k only running over the N bits of q rather than the L bits of p
+    //returns None if r or s is zero, in which case a new k is needed
+    fn sign_with_nonce(&self, z: &BigInt, k: &BigInt) -> Option<DsaSignature> {
+        let DsaParameters { p, q, g, .. } = &self.public_key.parameters;
+
+        let context = MontgomeryContext::new(p).expect("p is an odd prime");
+        let r = context.modpow_bounded(g, k, q.bits()).rem(q);
+        let k_inv = montgomery_modular_exponentiation(k.clone(), q - BigInt::from(2), q.clone());
+        let s = (k_inv * (z + &self.x * &r)).rem(q);
+
+        if r == BigInt::ZERO || s == BigInt::ZERO {
+            return None
+        }
+
+        Some(DsaSignature { r, s })
+    }
+}
+
+impl DsaPublicKey {
+    ///new() checks 1 < y < p and y^q = 1 mod p (SP 800-89 section 5.3.1)
+    pub fn new(parameters: DsaParameters, y: BigInt) -> Result<Self, DsaError> {
+        let one = BigInt::from(1);
+
+        if y <= one || y >= parameters.p || y.modpow(&parameters.q, &parameters.p) != one {
+            return Err(DsaError::InvalidPublicKey)
+        }
+
+        Ok(Self { parameters, y })
+    }
+
+    pub fn parameters(&self) -> &DsaParameters {
+        &self.parameters
+    }
+
+    pub fn y(&self) -> &BigInt {
+        &self.y
+    }
 
-        let b = alpha.modpow(&d, &p);
-        
-        let k_pub = DsaPublicKey { p, q, alpha, b};
-        let k_priv = DsaPrivatekey { d };
+    ///verify() hashes the message with the default hash for the parameter size, the
+    ///same one sign() uses, and checks the signature over it
+    pub fn verify(&self, message: &[u8], signature: &DsaSignature) -> bool {
+        match DefaultHash::for_size(self.parameters.size()) {
+            DefaultHash::Sha224 => self.verify_digest(Sha224::new_with_prefix(message), signature),
+            DefaultHash::Sha256 => self.verify_digest(Sha256::new_with_prefix(message), signature),
+        }
+    }
 
-        Self { k_pub, k_priv }
+    ///verify_digest() checks a signature over a hasher the caller has fed with update()
+    pub fn verify_digest<D: Digest>(&self, digest: D, signature: &DsaSignature) -> bool {
+        self.verify_prehashed::<D>(&digest.finalize(), signature)
     }
 
-    fn validate_ephemeral_key(&self, eph_key:&BigInt, primes: &DsaPrimePair) -> bool {
-        //validate_ephemeral_key() method checks 
-        // 1. 1 <= eph_key <= q - 1
-        // 2. gcd(eph_key, q) = 1
-        let p = primes.p;
-        let q = primes.q;
+    ///verify_prehashed() checks 0 < r, s < q and then (FIPS 186-4 section 4.7), with
+    ///z the leftmost min(N, outlen) bits of the digest
+    /// 1. w = s^-1 mod q
+    /// 2. u_1 = z*w mod q and u_2 = r*w mod q
+    /// 3. v = (g^u_1 * y^u_2 mod p) mod q
+    /// 4. the signature is valid when v = r
+    ///
+    ///a digest that isn't exactly one D output never verifies
+    pub fn verify_prehashed<D: Digest>(&self, digest: &[u8], signature: &DsaSignature) -> bool {
+        let DsaParameters { p, q, g, .. } = &self.parameters;
+        let (r, s) = (&signature.r, &signature.s);
 
-        //check size constaint
-        if BigInt::ZERO > ephemeral_key || ephemeral_key > (q-1) {
+        if digest.len() != <D as Digest>::output_size() {
             return false
-        };
-        
-        //ephemeral key needs to be coprime to q
-        if helper::gcd(&eph_key, &q) != BigInt::from(1) {
+        }
+        if r <= &BigInt::ZERO || r >= q || s <= &BigInt::ZERO || s >= q {
+            return false
+        }
+
+        let z = rfc6979::bits2int(digest, q.bits());
+        let Some(w) = s.modinv(q) else {
             return false
         };
+        let u_1 = (z * &w).rem(q);
+        let u_2 = (r * &w).rem(q);
 
-        true
+        let v = (g.modpow(&u_1, p) * self.y.modpow(&u_2, p)).rem(p).rem(q);
+
+        &v == r
     }
+}
 
-    fn generate_signature(&self, ephemeral_key: BigInt, primes: &DsaPrimePair, m: &BigInt) -> DsaSignature {
-        //generate_signature() method generates signature (r,s) using an ephemeral key (per-message secret)
-        //
-        //1. ensure ephemeral key satisfies size constraints
-        //2. compute r parameter by raising alpha to ephemeral key mod p mod q
-        //3. compute s parameter by 
-        //     - computing q-inverse of ephemeral key
-        //     - computing s = H(m) + d*r * eph_key_inv mod q
-        //4. return (r,s) wrapped in DsaSignature struct
+//hash used by sign() and verify(), the SHA-2 function whose output matches N. legacy
+//N = 160 keys keep SHA-256 (truncated to 160 bits) since SHA-1 isn't offered
+enum DefaultHash {
+    Sha224,
+    Sha256,
+}
 
-        let p = primes.p;
-        let q = primes.q;
-        if self.validate_ephemeral_key(&ephemeral_key, primes) == false {
-            panic!("ephemeral key does not respect constraints.")
+impl DefaultHash {
+    fn for_size(size: DsaParameterSize) -> DefaultHash {
+        match size {
+            DsaParameterSize::L2048N224 => DefaultHash::Sha224,
+            DsaParameterSize::L1024N160 | DsaParameterSize::L2048N256 | DsaParameterSize::L3072N256 => DefaultHash::Sha256,
         }
+    }
+}
+
+//SHA-256 digest read as a big-endian integer
+fn hash_to_int(bytes: &[u8]) -> BigInt {
+    BigInt::from_bytes_be(Sign::Plus, &Sha256::digest(bytes))
+}
 
-        let r = (self.k_pub.alpha.modpow(&ephemeral_key, &p)).rem(&q);
+//FIPS 186-4 A.1.1.2 steps 6-7: U = Hash(seed) mod 2^(N-1), and
+//q = 2^(N-1) + U + 1 - (U mod 2), an odd N-bit candidate
+fn generate_q(n: u64, seed: &[u8]) -> BigInt {
+    let top = BigInt::from(1) << (n - 1);
+    let u = hash_to_int(seed).rem(&top);
 
-        let hashed_message = hash(m);
+    let q = &top + &u + 1;
+    if u.bit(0) { q - 1 } else { q }
+}
 
-        let ephemeral_key_inv = ephemeral_key.modinv(&q).unwrap();
-        let s_ = (hashed_message + self.k_priv.d.checked_mul(&r).unwrap()).checked_mul(&ephemeral_key_inv).unwrap();
-        let s = s_.rem(&q);
+//FIPS 186-4 A.1.1.2 steps 9-11: for each counter, hash consecutive seed values into
+//an L-bit X and move it down to p = X - (X mod 2q) + 1 = 1 mod 2q. returns the first
+//probable prime p with the counter it was found at, or None if counter passes max
+fn generate_p(l: u64, q: &BigInt, seed: &[u8], max_counter: u32) -> Option<(BigInt, u32)> {
+    let seedlen = seed.len() as u64 * 8;
+    let seed_modulus = BigInt::from(1) << seedlen;
+    let seed_int = BigInt::from_bytes_be(Sign::Plus, seed);
 
-        DsaSignature { (r,s) }
+    //n + 1 hash outputs give the L bits of X, the last one cut to b bits
+    let n = l.div_ceil(OUTLEN) - 1;
+    let b = l - 1 - n*OUTLEN;
+    let top = BigInt::from(1) << (l - 1);
+    let two_q = q * 2;
+    let mut offset = BigInt::from(1);
 
+    for counter in 0..=max_counter {
+        let mut w = BigInt::ZERO;
+        for j in 0..=n {
+            let (_, bytes) = ((&seed_int + &offset + j).rem(&seed_modulus)).to_bytes_be();
+            let mut v_input = vec![0u8; seed.len() - bytes.len()];
+            v_input.extend_from_slice(&bytes);
+
+            let mut v = hash_to_int(&v_input);
+            if j == n {
+                v = v.rem(BigInt::from(1) << b);
+            }
+            w += v << (j*OUTLEN);
+        }
+
+        let x = w + &top;
+        let c = (&x).rem(&two_q);
+        let p = x - (c - 1);
+
+        if p >= top && is_prime(&p, PRIME_TEST_ROUNDS) {
+            return Some((p, counter))
+        }
+
+        offset += n + 1;
     }
 
+    None
 }
 
-// (r,s) pair object
-struct DsaSignature {
-    sig: (BigInt, BigInt)
+//FIPS 186-4 A.2.3: g = Hash(seed || "ggen" || index || count)^((p-1)/q) mod p for
+//count = 1, 2, ... until g >= 2. None if the 16-bit count runs out
+fn generate_g(p: &BigInt, q: &BigInt, seed: &[u8], index: u8) -> Option<BigInt> {
+    let e = (p - 1) / q;
+
+    for count in 1..=u16::MAX {
+        let mut u = seed.to_vec();
+        u.extend_from_slice(b"ggen");
+        u.push(index);
+        u.extend_from_slice(&count.to_be_bytes());
+
+        let g = hash_to_int(&u).modpow(&e, p);
+        if g >= BigInt::from(2) {
+            return Some(g)
+        }
+    }
+
+    None
 }
 
 impl DsaSignature {
+    pub fn new(r: BigInt, s: BigInt) -> Self {
+        Self { r, s }
+    }
+
+    pub fn r(&self) -> &BigInt {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigInt {
+        &self.s
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::{Sha384, Sha512};
+    use sha3::Sha3_256;
+
+    fn from_hex(hex: &str) -> BigInt {
+        BigInt::parse_bytes(hex.as_bytes(), 16).unwrap()
+    }
+
+    //(L, N) = (2048, 256) parameters and key generated by OpenSSL
+    fn parameters_2048_256() -> (DsaParameters, BigInt, BigInt) {
+        let p = from_hex(concat!(
+            "e6eb4f9cbeecc662b720a7c1a68d91a4875194b987d1de959ab17ca64976d14ac7e72c1bb91cad2d96eb27cbe1dd4758",
+            "90975b7b6fe01ae6c29e8aaedd32c3f9639bb3983d05dbe4c2ed79f817c7e9c2f41ee44250e295223aeaafdffbfc9db6",
+            "e3b553644426974ab1210abf78519bec2b80f8c031ce137334c189226ad56ba068a7ad108100928d1ece24016b8158eb",
+            "72da4a2cbe2abf2ce7cc8c79092fc860ead7f741e467c5e33ef2c74d2b0d97928c836220a4fde6b477884b047911443d",
+            "d8fc5d2db152ce114032cdfb40c49cd97e710799bae1bcb757756db5faf8c9ee2926081f9c5aededea6260b84e76ec43",
+            "2a020be351a4c888d526045aebb076af"));
+        let q = from_hex("c594a6def410cbe9a9bbc6443127dcf469979294a84a98754971baa929124ae5");
+        let g = from_hex(concat!(
+            "978822193c3e02e40b422269e6317aedb92e4629c8792e95aff949214cc1222cc969a89a348b097c674ec7c714817cbf",
+            "656ef184007961d8c0e31ffd87f32fdb1e4838bf2a7ef5b70541442f67ff94996e24d9fba11f1080521973d46e1ec70e",
+            "93ccc61f3c9e07989ea451fe45bf96ad85830834383a07f6608ea73be92a686dbd7f9ba6ea8c24357f6d220f65a55488",
+            "17d7cb7e8cabfabc9a46e8749d2f03a0aef2e00f0744763a790a094a7921bd2908a0301deadd8e56c0cbf5dc5c080796",
+            "7437b060b9591e6fb2e973b5d053890b65f17b608d3f1970e80a8e8f667f1486031e1ed203dec99d0fc322bd3666b26d",
+            "918042024acb204e245e1558960e6ed5"));
+        let x = from_hex("73f501ef4e64389839721ed7ac823a8dee1237b53b6d1acb45d8f56845a1ed0f");
+        let y = from_hex(concat!(
+            "dba504fdfd3d4eb8530e983b3e536c7b82c4e526f145f7c3d439a86786f82c9a9d1b1ee64c624ec45cf402967be51b88",
+            "8e6baea49dd6dd56f19d9f204f45fcac6aa0763bbdbb8954937f34fece3f814c67cf248e945a421814b8435fc3a768a5",
+            "a0ddb3441e04e89ec9c12bab68ee7cbd31f57aeaf9efbf8f8f9fefab5a4c94117d245459d52981d7f4a5fb87ca81ef07",
+            "4596ec5aea5cc888f0628ce03656ab2b044905d8a09ce843b03c3ee62a46523c3410620a3a4fe73ee6ec90a377fad92c",
+            "c892b662c7a3e4b2c88099072de7809a4779994f6032203088828863a3707fa1c5700456f95e951c01b7da3bfec89d46",
+            "61171f10865c8ab6f106b4b2488242b3"));
+
+        (DsaParameters::new(p, q, g).unwrap(), x, y)
+    }
+
+    //(L, N) = (2048, 224) parameters and key generated by OpenSSL. q is shorter than a
+    //SHA-256 digest, so SHA-256 signatures over it exercise hash truncation
+    fn parameters_2048_224() -> (DsaParameters, BigInt, BigInt) {
+        let p = from_hex(concat!(
+            "e893e08a9c60fbe14ebb1338ec7689ae2bee8f382a2bdd2c2ca9de53c83f994df4eeee38e640f789d025c278664b18dd",
+            "07340a7abc7730cfc50e290aface04d90f7ae571057133aaefc8ac68b6f1064dcfc440a26ad7dce7a5fd911ed5735fb3",
+            "71c39779e4b2fc90cf2d70a1d7e2e4f1427bd0ed65cce5e125fd8b47f4cb938e7d6c1da73a08fbdffa76db264a1a1057",
+            "89436b3826f1d311f3f7294b54e77abe9a0e3e4b7c2cbb88a40bbe741f768a2dde15cf763015ad878d8c6d0c48c907ec",
+            "e585b902387d0042524bf99998e3dd35470ca0694bc9a62056fadf0e8ae4bfd87a6e34ac626e77d75366682d0437c0a1",
+            "fd407ea811cbf2fc943a954d8a3b44eb"));
+        let q = from_hex("d2d2fcc5f2c83f8d5d454197b5e37b94db5494f0d8e2bcdfd77967f7");
+        let g = from_hex(concat!(
+            "a7d8c57e4d1bdeb59ce11db98cf43520872323d4a71ed81849b4166f23e0f48e9f45be14aa06e81c918d6b19ab239936",
+            "dab8a4e3d50a888924a9a94c762a54dbed6695a800a40e7b1e666a17e045dcb24291a738af4741d292e46a4174b537cd",
+            "5d7937cf44fe26877d98357ca72f58bbc3bd7a71f3e8d4e77c5a708bb376fed661c8ebdaad67e540ba726621ac1e4c2c",
+            "ae37fe8157dd951c61d1074c2a0974f547008b15c1035e79fb46320b051b013b4352d5a6de0cff9f1ebca9599d992d06",
+            "d77baa9b7495938031cb11aebd4cefd2c1a6a3372771fef7b368d25d1979508b82e68a76df9315cf9e4000b22da70608",
+            "a2df44463a7322cc57ec5b1c615c37c7"));
+        let x = from_hex("14d60d5b54c71bc3f9a5de5f1494198b965652e216de04b8d0841c8f");
+        let y = from_hex(concat!(
+            "be6b3b6d0678c90b05c1256874b0f22984bd42f98184b9fb4939a4b8208dc635a6969f8218cb97525a05f681e013136b",
+            "adba7e6e280d1cc1554084eae32e641fd6d9fdfee6b40fea3a39a17b6af76f4a9d1b1011896533d8eea204dd10460207",
+            "8959c471083cde779389e4bc146c60840ade44df8a62cbe8f03cd2c6c39604b3eaed2bd5b5565e6a0349a05666ce1263",
+            "5914575280fac8cfa9da3379ec61d8847f66bcf159f75b78ce54336a763777038a0ee628a6dd27c85d8ead08109e7f15",
+            "86e4517d18cd582529bfde53bdff8e390409a0286494b207ce3bd9edeec73fadf9c1c7c75ef0b4675b27d156d3335f6a",
+            "48f9897b734d28c3b202a3aa80d9f57f"));
+
+        (DsaParameters::new(p, q, g).unwrap(), x, y)
+    }
+
+    //(L, N) = (3072, 256) parameters and key generated by OpenSSL
+    fn parameters_3072_256() -> (DsaParameters, BigInt, BigInt) {
+        let p = from_hex(concat!(
+            "ffa7f42a149ccf252ed0cf36132b2e17feadfea4fcbb339bbd8dd943ea0f52c07217e50252a98ba09010cb215e9a972b",
+            "50ad5cdb8a95f48480a80b6f19e4dd762e4464b82e0be09cf9f36dc027a40c9b12266e2373d4c53cc184ab596ad743bc",
+            "b79d0102aea6b8ff0ec4008b37e25d8e19e952c50551d1fc50043799a95ddc8d75ab9294e96a623b9824aaf0376c0c15",
+            "e6130682922847a69b1f62fcf85c54137453e3b77b92604e765e43407cf06d75f75efdb1bf253ba7e59260ac7d13f8b0",
+            "879089a7675c525641acd682f4ce98d96f03e81d04171dacfe04cc0ba545efa2ea2c1ed6e5f85e3df58a0ef4f7640b70",
+            "f69795b957f24bf0adb0784abae1dbd60cfdc9245db0f4b09156216a4f90e7ea5518c4db2f01557a8d82e53f432bd71f",
+            "44fcf270ef20762dc7806101aa75a7b0f835de259040b2f605530ce871eb57c8de167636399468aa750ee1c167615523",
+            "6f6514c8a99d1da152e50f5f4d34ddf76d3bf71252342ded8ceeffce7b32c00d46a7e25c1ed3b796f4158795a62c97ef"));
+        let q = from_hex("f8f4aeb130db6560ed78f0407e6d04026b4cc12887bfefe6f77e10c33120f6ab");
+        let g = from_hex(concat!(
+            "cdac45559a02828257b81a0e2377f805d88ed9d5184657a3d7cfa24d6b230b7f9aba9a101f360aee637d522e48605216",
+            "7ef5b38a93f127094769fbc5f6eb506b0f53da28462eeb6595a621af5542ad1e7e9198fe09088fbf341985cfa7e248bf",
+            "4d95dcf92e930d3978e85bde3ef7914a488c628eb4c3ecada45cb2ac850a0044e88003b274ae943c8cf85f835d0d86a4",
+            "6f41d9402160dc03eaf63904a1ad21d06ecb04170559d8db619c1ff4d9fd475fcd556dd8bc74ce0b5e758ea999e1e9eb",
+            "96ff6f5dbae716785395fabeedfe03bd621304d831331c870c6b76fe28bdbd81df88b340d67b402b5e99adef29ceee89",
+            "3cf67305736813fe1d4b86f1050d1a3292fd440d6fa78c89d0aa1783fd1248fda8f05ab3069d0423f262f9ba2ed01a22",
+            "5a6552bbf18bf4563ffdbd02debf7d5ca355f80481b191e615b3cd6d69681b88cb2dcec5f182810803586b2df8233188",
+            "ffca53b38f57be5957b7c09ea18073242e69cdaab121867986850c8de46762031d98d4f568f34f8349bccf94f867a41b"));
+        let x = from_hex("1ec547987570afb16abe4c48707bde1194c6ec36aedbd3e7e7180c493bf6a0da");
+        let y = from_hex(concat!(
+            "dd9d84cacc4cf50ded91569cce1217b6a4c7798a5c37fc5ac506a98a251248500218b524d00a8375935b13c168bf0f24",
+            "41d1bfe441437f3c0ef0581e9b22c7b5bfe77e70f6d524053b9045a11d5210e261d7e258e5b516a3df4a669f1a3134b6",
+            "54cf090ed2d8d0ba183fed0a489a24ada94a71768db92a1c1f21936ac5c646ef17319ac6754e18a4e8587af9c143f621",
+            "84c60022de62d665a785431722a5c8ac28a8a6c54fb53b9b5afcb4c1f72b00396deca8296396a46a17bc560364b4d9a9",
+            "8c0964c90ffcad8b3ccd432425058d29b2454d12ad13973a80258592a588f375ece18075cab2e70ee46e55182a5649c1",
+            "c8538990a77b9ef2a6301be22dd872af2e79b8a786b9a7974e9e73eecd87ab21b1671c070954274c71fb2d2e1c2b788e",
+            "fb8935c0c74aa6d352b2f55f9c5e6a608046c0ce1c7b87d93b78612c001d2dfb74cb4c3028f3347da118c995ba2c1947",
+            "fb028a812cf2f9ea748174be6a09f647d0c942bdbed1967ea796723e82fcd95b2dbd466149885042a7f80414c6a34caa"));
+
+        (DsaParameters::new(p, q, g).unwrap(), x, y)
+    }
+
+    //legacy (L, N) = (1024, 160) parameters and key generated by OpenSSL
+    fn parameters_1024_160() -> (DsaParameters, BigInt, BigInt) {
+        let p = from_hex(concat!(
+            "a74105b09fd129fb3215d47acdb11cf46bca95490a5af69cbd54bb4bd9ae3673a403affdec4ed8ff130ab510b5ee3185",
+            "fd5233e6693dcea44c27d9ec23a4e2264b5a4c5ea718b9b9dbf5fc2bc5d7f638edcc0c794addbca06fe6a02436ead89b",
+            "957c4c97563bae3cbf1432062b309d11c36ae3cdbe1a22f52fe064bde8de3335"));
+        let q = from_hex("dc68d3078d7eac7144652de98931d3d47de23969");
+        let g = from_hex(concat!(
+            "ad65f5f7097147777fdd52241cf19840df95e1cc0ee1bbaf803f53e23d3df43259446f4887f7112fc2e4617b4a2ddc4c",
+            "86f83ec77269b7f4af6c506ede93ed9fd11db794f31f5645b89d7fe1ac1156f128d088ef9fb32dd01d3cca1ddc5f018d",
+            "a619527ffdbb4a34ad8f80a997d01483fa9ca4bf5e44f9777cecf2f1480cc93"));
+        let x = from_hex("7b2cf352fe27f049c60940ba69056f03b43a8840");
+        let y = from_hex(concat!(
+            "f1a6307c66a90cb2de3352bd4300f653251862000c03b5406258f153674edb253396784bc4a6d54b227d3347727c3e8b",
+            "302243c386d46d827bf9d79ee027d2950a289bcc24ff877c5d97a17a5340d450be97547651a863f96da756200f0960e2",
+            "9d5f87dc8d3bce42272d57247bd73e0004acf89d97e354f519239b0c3ab007e"));
 
-    fn verify_signature(&self, m: &BigInt, primes: &DsaPrimePair, key_pair: &DsaKeyPair) -> bool {
-        //verify_signature() method takes as input DsaSignature object, message m and primes p,q
-        //and returns boolean
-        //
-        //1. pull (r,s) from DsaSignature object and compute q-inverse of s
-        //2. compute parameter u_1 = s_inv * H(m) mod q
-        //3. compute parameter u_2 = s_inv * r mod q
-        //4. compute v = (alpha^u_1 mod p) * (B^u_2 mod p) mod p mod q
-        //5. check that r equals v
+        (DsaParameters::new(p, q, g).unwrap(), x, y)
+    }
+
+    //(L, N) = (2048, 256) key of RFC 6979 appendix A.2.2
+    fn parameters_rfc6979() -> (DsaParameters, BigInt, BigInt) {
+        let p = from_hex(concat!(
+            "9db6fb5951b66bb6fe1e140f1d2ce5502374161fd6538df1648218642f0b5c48c8f7a41aadfa187324b87674fa1822b0",
+            "0f1ecf8136943d7c55757264e5a1a44ffe012e9936e00c1d3e9310b01c7d179805d3058b2a9f4bb6f9716bfe6117c6b5",
+            "b3cc4d9be341104ad4a80ad6c94e005f4b993e14f091eb51743bf33050c38de235567e1b34c3d6a5c0ceaa1a0f368213",
+            "c3d19843d0b4b09dcb9fc72d39c8de41f1bf14d4bb4563ca28371621cad3324b6a2d392145bebfac748805236f5ca2fe",
+            "92b871cd8f9c36d3292b5509ca8caa77a2adfc7bfd77dda6f71125a7456fea153e433256a2261c6a06ed3693797e7995",
+            "fad5aabbcfbe3eda2741e375404ae25b"));
+        let q = from_hex("f2c3119374ce76c9356990b465374a17f23f9ed35089bd969f61c6dde9998c1f");
+        let g = from_hex(concat!(
+            "5c7ff6b06f8f143fe8288433493e4769c4d988ace5be25a0e24809670716c613d7b0cee6932f8faa7c44d2cb24523da5",
+            "3fbe4f6ec3595892d1aa58c4328a06c46a15662e7eaa703a1decf8bbb2d05dbe2eb956c142a338661d10461c0d135472",
+            "085057f3494309ffa73c611f78b32adbb5740c361c9f35be90997db2014e2ef5aa61782f52abeb8bd6432c4dd097bc54",
+            "23b285dafb60dc364e8161f4a2a35aca3a10b1c4d203cc76a470a33afdcbdd92959859abd8b56e1725252d78eac66e71",
+            "ba9ae3f1dd2487199874393cd4d832186800654760e1e34c09e4d155179f9ec0dc4473f996bdce6eed1cabed8b6f116f",
+            "7ad9cf505df0f998e34ab27514b0ffe7"));
+        let x = from_hex("69c7548c21d0dfea6b9a51c9ead4e27c33d3b3f180316e5bcab92c933f0e4dbc");
+        let y = from_hex(concat!(
+            "667098c654426c78d7f8201eac6c203ef030d43605032c2f1fa937e5237dbd949f34a0a2564fe126dc8b715c5141802c",
+            "e0979c8246463c40e6b6bdaa2513fa611728716c2e4fd53bc95b89e69949d96512e873b9c8f8dfd499cc312882561ade",
+            "cb31f658e934c0c197f2c4d96b05cbad67381e7b768891e4da3843d24d94cdfb5126e9b8bf21e8358ee0e0a30ef13fd6",
+            "a664c0dce3731f7fb49a4845a4fd8254687972a2d382599c9bac4e0ed7998193078913032558134976410b89d2c171d1",
+            "23ac35fd977219597aa7d15c1a9a428e59194f75c721ebcbcfae44696a499afa74e04299f132026601638cb87ab79190",
+            "d4a0986315da8eec6561c938996beadf"));
+
+        (DsaParameters::new(p, q, g).unwrap(), x, y)
+    }
+
+    //one [mod = L=.., N=.., SHA-..] group of the CAVP SigGen file
+    struct SigGenGroup {
+        hash: String,
+        parameters: DsaParameters,
+        vectors: Vec<SigGenVector>
+    }
+
+    struct SigGenVector {
+        message: Vec<u8>,
+        x: BigInt,
+        y: BigInt,
+        k: BigInt,
+        signature: DsaSignature
+    }
+
+    fn cavp_siggen_groups() -> Vec<SigGenGroup> {
+        let file = include_str!("../tests/data/dsa_siggen_186-3.txt");
+        let mut groups: Vec<SigGenGroup> = Vec::new();
+        let mut hash = String::new();
+        let mut fields: std::collections::HashMap<&str, &str> = std::collections::HashMap::new();
+
+        for line in file.lines() {
+            if let Some(header) = line.strip_prefix("[mod = ") {
+                hash = header.trim_end_matches(']').rsplit(", ").next().unwrap().to_string();
+                continue
+            }
+            let Some((name, value)) = line.split_once(" = ") else {
+                continue
+            };
+            fields.insert(name, value);
+
+            //G closes the domain parameters of a group and S closes a vector
+            match name {
+                "G" => {
+                    let parameters = DsaParameters::new(from_hex(fields["P"]), from_hex(fields["Q"]), from_hex(fields["G"])).unwrap();
+                    groups.push(SigGenGroup { hash: hash.clone(), parameters, vectors: Vec::new() });
+                },
+                "S" => {
+                    let vector = SigGenVector {
+                        message: hex::decode(fields["Msg"]).unwrap(),
+                        x: from_hex(fields["X"]),
+                        y: from_hex(fields["Y"]),
+                        k: from_hex(fields["K"]),
+                        signature: DsaSignature::new(from_hex(fields["R"]), from_hex(fields["S"]))
+                    };
+                    groups.last_mut().unwrap().vectors.push(vector);
+                },
+                _ => ()
+            }
+        }
+
+        groups
+    }
 
-        //unpack public information
-        let p = primes.p;
-        let q = primes.q;
-        let r = &self.sig.0;
-        let s_inv = &self.sig.1.modinv(&q).unwrap();
-        let hashed_message = hash(m);
-        let alpha = &key_pair.k_pub.alpha;
-        let b = &key_pair.k_pub.b;
+    #[test]
+    fn test_rfc6979_vectors() {
+        //RFC 6979 appendix A.2.2, the SHA-2 cases. SHA-1 isn't offered
+        let (parameters, x, y) = parameters_rfc6979();
+        let key_pair = DsaKeyPair::from_private_key(parameters.clone(), x).unwrap();
+        assert_eq!(key_pair.public_key().y(), &y);
+        let public_key = DsaPublicKey::new(parameters, y).unwrap();
 
-        let u_1 = s_inv.checked_mul(&hashed_message).unwrap().rem(&q);
-        let u_2 = s_inv.checked_mul(&r).unwrap().rem(&q);
+        let signature = DsaSignature::new(from_hex("dc9f4deada8d8ff588e98fed0ab690ffce858dc8c79376450eb6b76c24537e2c"), from_hex("a65a9c3bc7babe286b195d5da68616da8d47fa0097f36dd19f517327dc848cec"));
+        assert_eq!(key_pair.sign_digest(Sha224::new_with_prefix(b"sample")), signature);
+        assert!(public_key.verify_digest(Sha224::new_with_prefix(b"sample"), &signature));
 
-        let v = alpha.modpow(&u_1, &p)
-            .checked_mul(&b.modpow(&u_2, &p))
-            .unwrap()
-            .rem(&p)
-            .rem(&q);
+        let signature = DsaSignature::new(from_hex("272aba31572f6cc55e30bf616b7a265312018dd325be031be0cc82aa17870ea3"), from_hex("e9cc286a52cce201586722d36d1e917eb96a4ebdb47932f9576ac645b3a60806"));
+        assert_eq!(key_pair.sign_digest(Sha224::new_with_prefix(b"test")), signature);
+        assert!(public_key.verify_digest(Sha224::new_with_prefix(b"test"), &signature));
 
-        if r == v {
-            return true
+        let signature = DsaSignature::new(from_hex("eace8bdbbe353c432a795d9ec556c6d021f7a03f42c36e9bc87e4ac7932cc809"), from_hex("7081e175455f9247b812b74583e9e94f9ea79bd640dc962533b0680793a38d53"));
+        assert_eq!(key_pair.sign_digest(Sha256::new_with_prefix(b"sample")), signature);
+        assert!(public_key.verify_digest(Sha256::new_with_prefix(b"sample"), &signature));
+
+        let signature = DsaSignature::new(from_hex("8190012a1969f9957d56fccaad223186f423398d58ef5b3cefd5a4146a4476f0"), from_hex("7452a53f7075d417b4b013b278d1bb8bbd21863f5e7b1cee679cf2188e1ab19e"));
+        assert_eq!(key_pair.sign_digest(Sha256::new_with_prefix(b"test")), signature);
+        assert!(public_key.verify_digest(Sha256::new_with_prefix(b"test"), &signature));
+
+        let signature = DsaSignature::new(from_hex("b2da945e91858834fd9bf616ebac151edbc4b45d27d0dd4a7f6a22739f45c00b"), from_hex("19048b63d9fd6bca1d9bae3664e1bcb97f7276c306130969f63f38fa8319021b"));
+        assert_eq!(key_pair.sign_digest(Sha384::new_with_prefix(b"sample")), signature);
+        assert!(public_key.verify_digest(Sha384::new_with_prefix(b"sample"), &signature));
+
+        let signature = DsaSignature::new(from_hex("239e66ddbe8f8c230a3d071d601b6ffbdfb5901f94d444c6af56f732beb954be"), from_hex("6bd737513d5e72fe85d1c750e0f73921fe299b945aad1c802f15c26a43d34961"));
+        assert_eq!(key_pair.sign_digest(Sha384::new_with_prefix(b"test")), signature);
+        assert!(public_key.verify_digest(Sha384::new_with_prefix(b"test"), &signature));
+
+        let signature = DsaSignature::new(from_hex("2016ed092dc5fb669b8efb3d1f31a91eecb199879be0cf78f02ba062cb4c942e"), from_hex("d0c76f84b5f091e141572a639a4fb8c230807eea7d55c8a154a224400aff2351"));
+        assert_eq!(key_pair.sign_digest(Sha512::new_with_prefix(b"sample")), signature);
+        assert!(public_key.verify_digest(Sha512::new_with_prefix(b"sample"), &signature));
+
+        let signature = DsaSignature::new(from_hex("89ec4bb1400eccff8e7d9aa515cd1de7803f2daff09693ee7fd1353e90a68307"), from_hex("c9f0bdabcc0d880bb137a994cc7f3980ce91cc10faf529fc46565b15cea854e1"));
+        assert_eq!(key_pair.sign_digest(Sha512::new_with_prefix(b"test")), signature);
+        assert!(public_key.verify_digest(Sha512::new_with_prefix(b"test"), &signature));
+    }
+
+    //SigGen: the key pair gives Y, signing with the given K gives (R, S), and it verifies
+    fn check_siggen<D: Digest + BlockSizeUser>(group: &SigGenGroup) {
+        let q = group.parameters.q();
+
+        for vector in &group.vectors {
+            let digest = D::digest(&vector.message);
+            let key_pair = DsaKeyPair::from_private_key(group.parameters.clone(), vector.x.clone()).unwrap();
+            assert_eq!(key_pair.public_key().y(), &vector.y);
+
+            let z = rfc6979::bits2int(&digest, q.bits());
+            assert_eq!(key_pair.sign_with_nonce(&z, &vector.k).as_ref(), Some(&vector.signature));
+            assert!(key_pair.public_key().verify_prehashed::<D>(&digest, &vector.signature));
         }
+    }
+
+    //SigVer-style failures built from each SigGen vector: modified r, s, message and key
+    fn check_sigver_failures<D: Digest>(group: &SigGenGroup) {
+        let one = BigInt::from(1);
+
+        for (i, vector) in group.vectors.iter().enumerate() {
+            let digest = D::digest(&vector.message);
+            let public_key = DsaPublicKey::new(group.parameters.clone(), vector.y.clone()).unwrap();
+            let (r, s) = (vector.signature.r(), vector.signature.s());
 
-        false
+            assert!(!public_key.verify_prehashed::<D>(&digest, &DsaSignature::new(r + &one, s.clone())));
+            assert!(!public_key.verify_prehashed::<D>(&digest, &DsaSignature::new(r.clone(), s + &one)));
 
+            let mut message = vector.message.clone();
+            message[0] ^= 0x01;
+            assert!(!public_key.verify_prehashed::<D>(&D::digest(&message), &vector.signature));
 
+            //the key of the next vector in the group
+            let other_y = group.vectors[(i + 1) % group.vectors.len()].y.clone();
+            let other_key = DsaPublicKey::new(group.parameters.clone(), other_y).unwrap();
+            assert!(!other_key.verify_prehashed::<D>(&digest, &vector.signature));
+        }
     }
-}
 
+    #[test]
+    fn test_cavp_siggen_vectors() {
+        let groups = cavp_siggen_groups();
+        assert_eq!(groups.len(), 9);
 
-fn hash(m: &BigInt) -> BigInt {
-        //1. takes integer message m and converts to binary string
-        //
-        //2. instantiate sha256 hasher and pass in binary string
-        // 
-        //3. convert binary string output to integer hash
-        let message = format!("b{}", m);
-        let mut hasher = Sha256::new();
-        hasher.update(&message);
-        let hashed_m = hasher.finalize();
-        BigInt::from_bytes_be(num_bigint::Sign::Plus, &hashed_m)
+        for group in &groups {
+            assert_eq!(group.vectors.len(), 15);
+            match group.hash.as_str() {
+                "SHA-224" => check_siggen::<Sha224>(group),
+                "SHA-256" => check_siggen::<Sha256>(group),
+                "SHA-384" => check_siggen::<Sha384>(group),
+                "SHA-512" => check_siggen::<Sha512>(group),
+                other => panic!("unexpected hash {}", other)
+            }
+        }
     }
+
+    #[test]
+    fn test_cavp_sigver_expected_failures() {
+        for group in &cavp_siggen_groups() {
+            match group.hash.as_str() {
+                "SHA-224" => check_sigver_failures::<Sha224>(group),
+                "SHA-256" => check_sigver_failures::<Sha256>(group),
+                "SHA-384" => check_sigver_failures::<Sha384>(group),
+                "SHA-512" => check_sigver_failures::<Sha512>(group),
+                other => panic!("unexpected hash {}", other)
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_expected_failures() {
+        //tampered versions of RFC 6979 A.2.2 (SHA-256, "sample"), each of which has to fail
+        let (parameters, _, y) = parameters_rfc6979();
+        let q = parameters.q().clone();
+        let public_key = DsaPublicKey::new(parameters.clone(), y.clone()).unwrap();
+        let (r, s) = (from_hex("eace8bdbbe353c432a795d9ec556c6d021f7a03f42c36e9bc87e4ac7932cc809"),
+                      from_hex("7081e175455f9247b812b74583e9e94f9ea79bd640dc962533b0680793a38d53"));
+        let verify = |message: &[u8], r: &BigInt, s: &BigInt| {
+            public_key.verify_digest(Sha256::new_with_prefix(message), &DsaSignature::new(r.clone(), s.clone()))
+        };
+        let one = BigInt::from(1);
+
+        assert!(verify(b"sample", &r, &s));
+
+        //modified message, r and s
+        assert!(!verify(b"samplf", &r, &s));
+        assert!(!verify(b"sample", &(&r + &one), &s));
+        assert!(!verify(b"sample", &r, &(&s + &one)));
+        assert!(!verify(b"sample", &s, &r));
+
+        //r and s out of range: 0, q, and the valid value plus q
+        for bad in [BigInt::ZERO, q.clone(), -&r] {
+            assert!(!verify(b"sample", &bad, &s));
+            assert!(!verify(b"sample", &r, &bad));
+        }
+        assert!(!verify(b"sample", &(&r + &q), &s));
+        assert!(!verify(b"sample", &r, &(&s + &q)));
+
+        //the right signature under a different public key or hash
+        let other_y = y.modpow(&BigInt::from(2), parameters.p());
+        let other_key = DsaPublicKey::new(parameters, other_y).unwrap();
+        assert!(!other_key.verify_digest(Sha256::new_with_prefix(b"sample"), &DsaSignature::new(r.clone(), s.clone())));
+        assert!(!public_key.verify_digest(Sha384::new_with_prefix(b"sample"), &DsaSignature::new(r, s)));
+    }
+
+    #[test]
+    fn test_deterministic_signature_vectors() {
+        //signatures under each size's default hash (SHA-224 for N = 224, SHA-256 otherwise)
+        //with RFC 6979 nonces, from OpenSSL 3.5 (pkeyutl -pkeyopt nonce-type:1)
+        let vectors = [
+            (parameters_2048_224(), [
+                ("sample",
+                 "3b249899637c768ed3fdf47abd5b998af01f9d38e3a004403a34cef8",
+                 "cddcdf6b543560a38f721f5ebdf151a85757e469e96b8a6ef394d6d6"),
+                ("test",
+                 "ca4659a5dc8e0826bf79b92357d2e91f982dfaec75a23b05ca53a1fb",
+                 "a227cf6649ff680074fc03edc33401e8ef6ea98716fd9f62b2a1cae3"),
+            ]),
+            (parameters_2048_256(), [
+                ("sample",
+                 "3541e65692ce9c0d5fc4e7103e81b5b3394847d9874cb1c8e72e0ad91d6cf2fb",
+                 "53f986b37b1038e470e0b6831c0afd857382a94b2bcb3729b4d18c173b44e382"),
+                ("test",
+                 "bd2f51daaa867695cb3480afc58b8b7278cd46a4900a4d09a4672434f184f2c9",
+                 "9ef06595a55ada682b778a0d84efb31ee23ae78e715531f8f4beb17f380b4de0"),
+            ]),
+            (parameters_3072_256(), [
+                ("sample",
+                 "46745b96e4e216af9ce22a2cd5ec7c63a4cb78a89a7364ec099ef1de76c367ac",
+                 "eb92d5b72ab78b6a3721b69deab5183a73dfacdd2bb682c4031e019a72cb7c6b"),
+                ("test",
+                 "85f8ca8b3bac48562beb7b0a6d1dfa80b01e050a68388c3919700131a909dd27",
+                 "c68d086191d5cae6dc376c104ee5fc475d6107050e1bf9772caf8e50415d1037"),
+            ]),
+        ];
+
+        for ((parameters, x, y), signatures) in vectors {
+            let key_pair = DsaKeyPair::from_private_key(parameters.clone(), x).unwrap();
+            assert_eq!(key_pair.public_key().y(), &y);
+
+            let public_key = DsaPublicKey::new(parameters, y).unwrap();
+            for (message, r, s) in signatures {
+                let expected = DsaSignature::new(from_hex(r), from_hex(s));
+                assert_eq!(key_pair.sign(message.as_bytes()), expected);
+                assert!(public_key.verify(message.as_bytes(), &expected));
+                assert!(!public_key.verify(b"other message", &expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_legacy_parameters_verify_only() {
+        let mut rng = rand::rng();
+        let (parameters, x, y) = parameters_1024_160();
+        assert_eq!(parameters.size(), DsaParameterSize::L1024N160);
+
+        //OpenSSL signatures over "sample" and "test" still verify
+        let public_key = DsaPublicKey::new(parameters.clone(), y).unwrap();
+        let sample = DsaSignature::new(
+            from_hex("39242da9f9d1bcc4736bfccce3b2af6652c81ede"),
+            from_hex("6b12142843f53876528acfc5a7ec5b73141ac86b")
+        );
+        let test = DsaSignature::new(
+            from_hex("56265eb2f5865fde604791a0becc2a2866029b78"),
+            from_hex("9b2503580124d5d2838ed308c09e355fd88591f7")
+        );
+        assert!(public_key.verify(b"sample", &sample));
+        assert!(public_key.verify(b"test", &test));
+        assert!(!public_key.verify(b"test", &sample));
+
+        //but no new keys or parameters
+        assert_eq!(DsaKeyPair::from_private_key(parameters.clone(), x), Err(DsaError::LegacyParameters));
+        assert_eq!(DsaKeyPair::generate(&parameters, &mut rng), Err(DsaError::LegacyParameters));
+        assert_eq!(DsaParameters::generate(DsaParameterSize::L1024N160, &mut rng), Err(DsaError::LegacyParameters));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = rand::rng();
+        let (parameters, _, _) = parameters_2048_256();
+        let key_pair = DsaKeyPair::generate(&parameters, &mut rng).unwrap();
+        let public_key = key_pair.public_key();
+
+        let signature = key_pair.sign(b"DSA test message");
+        assert!(public_key.verify(b"DSA test message", &signature));
+        assert!(!public_key.verify(b"DSA test message!", &signature));
+
+        //hedged signatures differ each time but still verify
+        let hedged = key_pair.sign_hedged(b"DSA test message", &mut rng);
+        assert_ne!(hedged, signature);
+        assert!(public_key.verify(b"DSA test message", &hedged));
+
+        //r and s must lie in [1, q-1]
+        let q = parameters.q();
+        for (r, s) in [
+            (BigInt::ZERO, signature.s().clone()),
+            (signature.r().clone(), BigInt::ZERO),
+            (signature.r() + q, signature.s().clone()),
+            (signature.r().clone(), signature.s() + q),
+        ] {
+            assert!(!public_key.verify(b"DSA test message", &DsaSignature::new(r, s)));
+        }
+
+        let other = DsaKeyPair::generate(&parameters, &mut rng).unwrap();
+        assert!(!other.public_key().verify(b"DSA test message", &signature));
+    }
+
+    #[test]
+    fn test_digest_and_prehashed() {
+        let (parameters, x, _) = parameters_2048_224();
+        let key_pair = DsaKeyPair::from_private_key(parameters, x).unwrap();
+        let public_key = key_pair.public_key();
+        let signature = key_pair.sign(b"sample");
+
+        //a message fed to the hasher in pieces signs the same as the whole message
+        let mut digest = Sha224::new();
+        digest.update(b"sam");
+        digest.update(b"ple");
+        assert_eq!(key_pair.sign_digest(digest.clone()), signature);
+        assert!(public_key.verify_digest(digest, &signature));
+
+        let prehashed = Sha224::digest(b"sample");
+        assert_eq!(key_pair.sign_prehashed::<Sha224>(&prehashed), Ok(signature.clone()));
+        assert!(public_key.verify_prehashed::<Sha224>(&prehashed, &signature));
+
+        //the digest must be exactly one output of the named hash
+        assert_eq!(key_pair.sign_prehashed::<Sha256>(&prehashed), Err(DsaError::InvalidDigestLength));
+        assert!(!public_key.verify_prehashed::<Sha256>(&prehashed, &signature));
+    }
+
+    #[test]
+    fn test_hash_selection() {
+        //RFC 6979 signatures over "sample" under hashes other than the default, from
+        //OpenSSL 3.5 (pkeyutl -pkeyopt digest:<hash> -pkeyopt nonce-type:1)
+        let (parameters, x, _) = parameters_2048_224();
+        let key_pair = DsaKeyPair::from_private_key(parameters, x).unwrap();
+        let sha256 = DsaSignature::new(
+            from_hex("aa5bed7471e44a2ec30e9ccd51b6332a4ae798c1ad21c2dcb629a203"),
+            from_hex("35417ea2daa380c414283bf42096b8ecb2ef23a1c5a73fbe6c3094d0")
+        );
+        assert_eq!(key_pair.sign_digest(Sha256::new_with_prefix(b"sample")), sha256);
+        assert!(key_pair.public_key().verify_digest(Sha256::new_with_prefix(b"sample"), &sha256));
+        assert!(!key_pair.public_key().verify(b"sample", &sha256));
+
+        //N = 256 with a longer hash (truncated to 256 bits) and with SHA3-256
+        let (parameters, x, _) = parameters_2048_256();
+        let key_pair = DsaKeyPair::from_private_key(parameters, x).unwrap();
+        let public_key = key_pair.public_key();
+        let sha512 = DsaSignature::new(
+            from_hex("5fc819224987ae57eb43141a8e6d946548f953ea97d3efacb6a67748c764a262"),
+            from_hex("71f01d511f187abd3bc18ec60febce242276704ae88f045036e860b0b883d320")
+        );
+        let sha3_256 = DsaSignature::new(
+            from_hex("a180aa99a6ab9e90fa3301907028e6d904248a4ff95064626936271520fc921a"),
+            from_hex("ad73ae71e4d1e0b2e872a053810e6fa6616b9e1eb89ac3920d9e48261bf500d7")
+        );
+        assert_eq!(key_pair.sign_digest(Sha512::new_with_prefix(b"sample")), sha512);
+        assert_eq!(key_pair.sign_prehashed::<Sha3_256>(&Sha3_256::digest(b"sample")), Ok(sha3_256.clone()));
+        assert!(public_key.verify_digest(Sha512::new_with_prefix(b"sample"), &sha512));
+        assert!(public_key.verify_prehashed::<Sha3_256>(&Sha3_256::digest(b"sample"), &sha3_256));
+        assert!(!public_key.verify_digest(Sha3_256::new_with_prefix(b"sample"), &sha512));
+
+        let mut rng = rand::rng();
+        let hedged = key_pair.sign_digest_hedged(Sha3_256::new_with_prefix(b"sample"), &mut rng);
+        assert_ne!(hedged, sha3_256);
+        assert!(public_key.verify_digest(Sha3_256::new_with_prefix(b"sample"), &hedged));
+    }
+
+    //(L, N) = (2048, 256) parameters generated by OpenSSL 3.5 with FIPS 186-4 A.1.1.2
+    //and SHA-256 (genpkey -algorithm DHX -pkeyopt type:fips186_4 -pkeyopt gindex:1)
+    fn verifiable_parameters(validation: DsaValidationParameters) -> Result<DsaParameters, DsaError> {
+        let p = from_hex(concat!(
+            "ea5c4cf0ef8bedf9495a6a8fecba59c06429c4b34d84de91b3300bad3c72cdb3dfd117b923869a8c1992344a1f57cb6b",
+            "08d7c5d73d808a22a78aedd172f21732232505bcf60ba11214e8dae693170aff6278e9c0e7d5c76adfcbedc7b9054f50",
+            "85990f160d52ed43f2d7848c8eee39114cd651db6f98086bbc289bcec54fed5eaeb88c98a02c5481ab2fa18f69058bd4",
+            "afe3e02e34a3de8c6fa368ab4926261ff6c5cc350ceacbf10823255fb40f38bbc9b3a637bc77e8e4723f80fe27d3d5df",
+            "8f07f39cfd7c77141ec036f1ff277a1b5775681e8595598566e521d1fc362c2754f7631b633d0731af42bf79466a35a0",
+            "89bf0b3e47edbdd531543b53fd793b41"));
+        let q = from_hex("e5d9264ceb87c0a9ed3aed2adbb595ac6b4daf10457eb4f829bf7ac5c12cff39");
+        let g = from_hex(concat!(
+            "16cc47906f3d9ff429e53ce6c3621ad7678da3c1bef65616161b7339d24ae82e9465126156c5a8219aa96489c92eb228",
+            "950f79ee0bf7257a29583237579e277178d65f685d3268a0c901dc32d49f702b1f0cfbda36869935ebb10b48d632ac1b",
+            "04197057d1908601c93b8f56ad27c29fc426c97b5e79bce692388aca2768d9cdf03c68e3ca0879b1a236bdcc88b2aa44",
+            "34355f5e1e497c37f3842e3de0e95e46ddd35af790bc8ad98b566bdbfd53575c20b91dcc715be1e0c3735d01003fab97",
+            "be28c4ad9af5c80d06541b6885bec7469b15e319219fbd89c365ee40dc7c1393f1c12cd5bf4b5d0539fd3e213d756d7a",
+            "170d673a7fcb5e7041d364409ae0ec91"));
+
+        DsaParameters::new_verifiable(p, q, g, validation)
+    }
+
+    const OPENSSL_SEED: &str = "2787d46a0186ece40b232fe200798aedbadface9244c4da278f0577b2a9a1344";
+
+    #[test]
+    fn test_validate_generated_parameters() {
+        let seed = hex::decode(OPENSSL_SEED).unwrap();
+
+        let parameters = verifiable_parameters(DsaValidationParameters::new(seed.clone(), 67, 1)).unwrap();
+        assert_eq!(parameters.validate(), Ok(()));
+
+        //wrong counter, wrong index and a modified seed all fail to re-derive p, q and g
+        let mut other_seed = seed.clone();
+        other_seed[31] ^= 1;
+        for validation in [
+            DsaValidationParameters::new(seed.clone(), 66, 1),
+            DsaValidationParameters::new(seed.clone(), 68, 1),
+            DsaValidationParameters::new(seed.clone(), 67, 2),
+            DsaValidationParameters::new(other_seed, 67, 1),
+            DsaValidationParameters::new(seed[..16].to_vec(), 67, 1),
+        ] {
+            assert_eq!(verifiable_parameters(validation).unwrap().validate(), Err(DsaError::ParameterValidation));
+        }
+
+        let (parameters, _, _) = parameters_2048_256();
+        assert_eq!(parameters.validate(), Err(DsaError::MissingValidationParameters));
+    }
+
+    #[test]
+    fn test_generate_parameters() {
+        let mut rng = rand::rng();
+        let parameters = DsaParameters::generate(DsaParameterSize::L2048N224, &mut rng).unwrap();
+
+        assert_eq!(parameters.p().bits(), 2048);
+        assert_eq!(parameters.q().bits(), 224);
+        assert_eq!(parameters.validation_parameters().unwrap().seed().len(), 28);
+        assert_eq!(parameters.validate(), Ok(()));
+
+        let key_pair = DsaKeyPair::generate(&parameters, &mut rng).unwrap();
+        let signature = key_pair.sign(b"generated parameters");
+        assert!(key_pair.public_key().verify(b"generated parameters", &signature));
+    }
+
+    #[test]
+    fn test_invalid_parameters_and_keys() {
+        let (parameters, _, y) = parameters_2048_256();
+        let (p, q, g) = (parameters.p().clone(), parameters.q().clone(), parameters.g().clone());
+        let one = BigInt::from(1);
+
+        //q does not divide p - 1
+        assert_eq!(DsaParameters::new(p.clone(), &q + 2, g.clone()), Err(DsaError::InvalidParameters));
+        //unapproved sizes: (2048, 160) and the toy group p = 23, q = 11
+        assert_eq!(DsaParameters::new(p.clone(), from_hex("dc68d3078d7eac7144652de98931d3d47de23969"), g.clone()), Err(DsaError::InvalidParameters));
+        assert_eq!(DsaParameters::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)), Err(DsaError::InvalidParameters));
+        //g of order 1 and g not of order q
+        assert_eq!(DsaParameters::new(p.clone(), q.clone(), one.clone()), Err(DsaError::InvalidParameters));
+        assert_eq!(DsaParameters::new(p.clone(), q.clone(), BigInt::from(2)), Err(DsaError::InvalidParameters));
+        assert_eq!(DsaParameters::new(p.clone(), q.clone(), &p - &one), Err(DsaError::InvalidParameters));
+
+        assert_eq!(DsaKeyPair::from_private_key(parameters.clone(), BigInt::ZERO), Err(DsaError::InvalidPrivateKey));
+        assert_eq!(DsaKeyPair::from_private_key(parameters.clone(), q.clone()), Err(DsaError::InvalidPrivateKey));
+
+        assert_eq!(DsaPublicKey::new(parameters.clone(), one.clone()), Err(DsaError::InvalidPublicKey));
+        assert_eq!(DsaPublicKey::new(parameters.clone(), &y + &p), Err(DsaError::InvalidPublicKey));
+        assert_eq!(DsaPublicKey::new(parameters, &p - &one), Err(DsaError::InvalidPublicKey));
+    }
+
+    #[test]
+    fn test_composite_q() {
+        //2048-bit prime p with q | p - 1, but q = 3 * (odd 254-bit number). the
+        //structure checks alone accept it
+        let p = from_hex(concat!(
+            "de7d81b9dbbf6aacf25931d3dba66e6d76fa54a77c00c3aea5b420b81e29dcf0837d8b76711ee31812021b7f41f1426c",
+            "36f3bf538cd2ac001a6cf4e4e2d3d3ea240f010254cbb40e7990af1b13a11ec9df0ab42078253ed6e71d74680884b857",
+            "092277842e3aae30ca8bbe02ab8769849835b504a08ef3065c57cf3aafa2571c773b67977200c682556e62d2eed3406b",
+            "4b51f8a7e4d5d35f6e1d2f3e276b8e86db97f0958930c218f4bd106c42bcf0b4f0ec99b4e0fa3b6e24ae9c4b884ec384",
+            "d784860cf8a18f2eea0ff52a54fa4047028d827718ad4288fc0d4d3f57fd1238b58c3d444f63c6902b12bada3183e07e",
+            "6f2a1bf03db57c79ba1575ade85fc8cd"));
+        let q = from_hex("cf6ad79ffc9e2421decbce091a2a71e15355549fd900eb4c38660e33a007c53f");
+        let g = from_hex(concat!(
+            "2dfe0d4678ee110a9035105808e7ce9f69178fd153bfee216342759ef95a6f3c35542e47db3c8e6a6bfb48e00a7dd72a",
+            "1d273929e4f2efb5051cc42c290cd17d6c4e714877c60d56fa937d64e18d6a739487d98e98e94c2ec73458a27fe31da1",
+            "66494ab03ef63d4eb29a40e329b8dc36d64dfa23ed9a7f735b2fc2c453a85358c5fbe432ee7bc260e39a37ead01a277b",
+            "2297e1d71067cfbac82de86f43755a2adfc89039383a9e85109f03497f6d49e602c3f4a0cf2e0e03cf0850c07ccdb96e",
+            "04e0924f7c8a9e6d8737123cd6abcc1347553e9379805f026f5481659ba0fcb1de2cca8b8679f44b9a62cea7fe2ee77c",
+            "ebb06c14dc5c30c8ee716e0657e464fe"));
+        let y = from_hex(concat!(
+            "63943f0a1916bb2b8f911a0bc524828113505c819a211449f67f2de6b57c7bb14cc787bb844f9275463c9d1ca634d51c",
+            "b17d04217b6381a8a3d53c75bc1b796efb3a884d2d0196e40686a594f477e3a78c2395cb585ecb54879b35c0fa7c3c3f",
+            "f0e238d6a56f376a8082f0bdc9938de30f182c13d91cd04c01ebbfb32a8d284673d7e5cbc34033e0b3847605222cd2d4",
+            "a3bc788cd15429fa2628b26ce5311ba817e402c9532832e964bd9201608acd259da81c490927cf981a896e10beccb4d9",
+            "acc7d5c7b3c25e2c459e616f28d7f9bb556bdb36040e9ccae84f090e28de06c6ee4353fa52cc8272f3913f9ac4d386af",
+            "50785a713778d8cef1833b23bc535cf1"));
+
+        assert_eq!(DsaParameters::new(p.clone(), q.clone(), g.clone()), Err(DsaError::InvalidParameters));
+
+        //a key built around the check must still reject s = 3, which has no inverse mod q
+        let parameters = DsaParameters { p, q, g, validation: None };
+        let public_key = DsaPublicKey { parameters, y };
+        let signature = DsaSignature::new(BigInt::from(2), BigInt::from(3));
+
+        assert!(!public_key.verify(b"sample", &signature));
+    }
+}
@@ -1,110 +1,350 @@
-use num_bigint::BigInt;
-use crate::elliptic_curve_primitives::EC;
-
-fn main() {
-    // example with
-    // E: y^2 = x^3 + 2x + 2 mod 17, and generator point G = (5,11) 
-
-    //define public parameters
-    let G = (BigInt::from(5), BigInt::from(1));
-    let p = BigInt::from(17);
-    let a = BigInt::from(2);
-    let b = BigInt::from(2);
-
-    //Alice and Bob have access to generator of EC group, prime p and EC equation coefficients a and b
-    let sc = SharedConfig {
-        generator: G,
-        prime: p,
-        param_a: a,
-        param_b: b
-    };
+use num_bigint::{BigInt, Sign};
+use rand::CryptoRng;
+use zeroize::Zeroize;
+use crate::elliptic_curve_primitives::{DomainParameters, EncodingError, Point};
+use crate::rng;
+use std::fmt;
+
+
+//elliptic curve Diffie-Hellman (NIST SP 800-56A section 5.7.1.2)
+//
+//each party draws an EphemeralSecret d on the agreed curve, sends its PublicKey
+//Q = d*G, and combines the peer's Q' into the SharedSecret, the x coordinate of d*Q'.
+//both sides reach the same point d*d'*G
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcdhError {
+    PointAtInfinity,
+    CoordinateOutOfRange,
+    PointNotOnCurve,
+    PointNotInSubgroup,
+    InvalidEncoding,
+    CurveMismatch,
+}
+
+impl fmt::Display for EcdhError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcdhError::PointAtInfinity => write!(f, "public point is the point at infinity"),
+            EcdhError::CoordinateOutOfRange => write!(f, "public point coordinates must satisfy 0 <= x, y < p"),
+            EcdhError::PointNotOnCurve => write!(f, "public point does not lie on the curve"),
+            EcdhError::PointNotInSubgroup => write!(f, "public point is not in the subgroup generated by G"),
+            EcdhError::InvalidEncoding => write!(f, "public key is not a valid SEC 1 point encoding"),
+            EcdhError::CurveMismatch => write!(f, "peer public key is on different domain parameters"),
+        }
+    }
+}
+
+impl std::error::Error for EcdhError {}
+
+impl From<EncodingError> for EcdhError {
+    fn from(error: EncodingError) -> EcdhError {
+        match error {
+            EncodingError::CoordinateOutOfRange => EcdhError::CoordinateOutOfRange,
+            EncodingError::PointNotOnCurve => EcdhError::PointNotOnCurve,
+            EncodingError::InvalidLength | EncodingError::InvalidTag => EcdhError::InvalidEncoding,
+        }
+    }
+}
 
-    //Alice chooses secret alpha = 3
-    let p1 = PartyOne {
-        cyclic_group: &sc,
-        alpha: BigInt::from(3)
-    };
 
-    //Bob chooses secret beta = 11
-    let p2 = PartyTwo {
-        cyclic_group: &sc,
-        beta: BigInt::from(11)
+///validate_public_point() is the full public key validation of NIST SP 800-56A
+///section 5.6.2.3.3, run on the peer's point before it is multiplied by a secret
+///
+/// checks:
+///     - Q != O
+///     - 0 <= x, y < p
+///     - Q lies on E, so a point on a weaker curve y^2 = x^3 + ax + b' can't be
+///       used to learn the secret scalar (invalid-curve attack)
+///     - n*Q = O, so Q can't sit in a small subgroup of order dividing the
+///       cofactor (small-subgroup attack). when h = 1 every point on E has order n
+///       and the multiplication is skipped
+pub fn validate_public_point(domain: &DomainParameters, point: &Point) -> Result<(), EcdhError> {
+    let curve = domain.curve();
+
+    let (x, y) = match point {
+        Point::Identity => return Err(EcdhError::PointAtInfinity),
+        Point::Affine(x, y) => (x, y)
     };
 
-    //Alice computes alpha*G on the EC group and sends result to Bob
-    let p1_public = p1.config();
-    //Bob computes beta*G on same group of points and sends result to Alice
-    let p2_public = p2.config();
+    let in_range = |c: &BigInt| c >= &BigInt::ZERO && c < curve.p();
+    if !in_range(x) || !in_range(y) {
+        return Err(EcdhError::CoordinateOutOfRange)
+    }
+
+    if !curve.is_on_curve(point) {
+        return Err(EcdhError::PointNotOnCurve)
+    }
+
+    if domain.cofactor() != &BigInt::from(1) {
+        let n_q = curve.point_n_addition_vartime(point.clone(), domain.order().clone());
+        if !n_q.is_identity() {
+            return Err(EcdhError::PointNotInSubgroup)
+        }
+    }
+
+    Ok(())
+}
 
-    //Alice computes alpha*(beta*G)
-    let p1_private = p1.compute_shared_key(p2_public);
-    //Bob computes beta*(alpha*G)
-    let p2_private = p2.compute_shared_key(p1_public);
 
-    //check both parties have the same result
-    assert_eq!(p1_private, p2_private);
+///single-use ECDH private key
+///
+/// fields:
+///     - domain = curve domain parameters
+///     - scalar = d in [1, n-1], as a big-endian string the length of n
+///
+///the scalar bytes are overwritten with zeros on drop. the BigInt copies made for
+///each scalar multiplication are not, since num-bigint has no zeroize support
+pub struct EphemeralSecret {
+    domain: DomainParameters,
+    scalar: Vec<u8>
 }
 
-#[derive(Debug)]
-struct SharedConfig {
-    generator: (BigInt, BigInt),
-    prime: BigInt,
-    param_a: BigInt,
-    param_b: BigInt
+///ECDH public key: a validated point Q = d*G together with its domain parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    domain: DomainParameters,
+    point: Point
 }
-#[derive(Debug)]
-struct PartyOne<'a> {
-    cyclic_group: &'a SharedConfig,
-    alpha: BigInt
+
+///x coordinate of the shared point as a big-endian string of the field length,
+///overwritten with zeros on drop. it should go through a key derivation function
+///before being used as a key
+pub struct SharedSecret {
+    bytes: Vec<u8>
 }
-#[derive(Debug)]
-struct PartyTwo<'a> {
-    cyclic_group: &'a SharedConfig,
-    beta: BigInt
+
+//only the curve is printed so the scalar can't leak through logs
+impl fmt::Debug for EphemeralSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EphemeralSecret")
+            .field("domain", &self.domain)
+            .finish_non_exhaustive()
+    }
 }
 
-impl<'a> PartyOne<'a> {
-    fn config(&self) -> (BigInt, BigInt) {
-        let G: (BigInt, BigInt) = self.cyclic_group.generator.clone();
-        let p: BigInt = self.cyclic_group.prime.clone();
-        let a: BigInt = self.cyclic_group.param_a.clone();
-        let b: BigInt = self.cyclic_group.param_b.clone();
-        
-        let E: EC = EC::new(a, b, p);
-        let p1_public: (BigInt, BigInt) = EC::point_n_addition(&E, G, self.alpha.clone());
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSecret").finish_non_exhaustive()
+    }
+}
 
-        p1_public
+impl Drop for EphemeralSecret {
+    fn drop(&mut self) {
+        self.scalar.zeroize();
     }
+}
 
-    fn compute_shared_key(&self, p2_public: (BigInt, BigInt)) -> (BigInt, BigInt) {
-        let p: BigInt = self.cyclic_group.prime.clone();
-        let a: BigInt = self.cyclic_group.param_a.clone();
-        let b: BigInt = self.cyclic_group.param_b.clone();
-        
-        let E: EC = EC::new(a, b, p);
-        EC::point_n_addition(&E, p2_public, self.alpha.clone())
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
     }
 }
 
-impl<'a> PartyTwo<'a> {
-    fn config(&self) -> (BigInt, BigInt) {
-        let G: (BigInt, BigInt) = self.cyclic_group.generator.clone();
-        let p: BigInt = self.cyclic_group.prime.clone();
-        let a: BigInt = self.cyclic_group.param_a.clone();
-        let b: BigInt = self.cyclic_group.param_b.clone();
-        
-        let E: EC = EC::new(a, b, p);
-        let p1_public: (BigInt, BigInt) = EC::point_n_addition(&E, G, self.beta.clone());
+impl EphemeralSecret {
+    ///random() draws d uniformly from [1, n-1] on the given domain parameters or
+    ///NamedCurve, e.g. EphemeralSecret::random(NamedCurve::P256, &mut rng)
+    pub fn random<R: CryptoRng + ?Sized>(domain: impl Into<DomainParameters>, rng: &mut R) -> Self {
+        let domain = domain.into();
+        let (_, bound) = (domain.order() - BigInt::from(1)).into_parts();
+        let d = BigInt::from(rng::generate_random_below(&bound, rng)) + BigInt::from(1);
+
+        Self::from_scalar(domain, &d)
+    }
+
+    //fixed scalar, for examples with known values
+    fn from_scalar(domain: DomainParameters, d: &BigInt) -> Self {
+        let len = domain.order().bits().div_ceil(8) as usize;
+        let (_, bytes) = d.to_bytes_be();
+        let mut scalar = vec![0u8; len - bytes.len()];
+        scalar.extend_from_slice(&bytes);
+
+        Self { domain, scalar }
+    }
+
+    fn scalar(&self) -> BigInt {
+        BigInt::from_bytes_be(Sign::Plus, &self.scalar)
+    }
+
+    ///public_key() returns Q = d*G
+    pub fn public_key(&self) -> PublicKey {
+        let curve = self.domain.curve();
+        let point = curve.point_n_addition(self.domain.generator().clone(), self.scalar())
+            .expect("DomainParameters::new checked the field size");
 
-        p1_public
+        PublicKey { domain: self.domain.clone(), point }
     }
 
-    fn compute_shared_key(&self, p1_public: (BigInt, BigInt)) -> (BigInt, BigInt) {
-        let p: BigInt = self.cyclic_group.prime.clone();
-        let a: BigInt = self.cyclic_group.param_a.clone();
-        let b: BigInt = self.cyclic_group.param_b.clone();
-        
-        let E: EC = EC::new(a, b, p);
-        EC::point_n_addition(&E, p1_public, self.beta.clone())
+    ///diffie_hellman() consumes the secret and returns the x coordinate of d*Q' for the
+    ///peer's public key Q', which was validated when it was constructed
+    pub fn diffie_hellman(self, peer: &PublicKey) -> Result<SharedSecret, EcdhError> {
+        if peer.domain != self.domain {
+            return Err(EcdhError::CurveMismatch)
+        }
+
+        let curve = self.domain.curve();
+        let shared = curve.point_n_addition(peer.point.clone(), self.scalar())
+            .expect("DomainParameters::new checked the field size");
+        let x = shared.x().ok_or(EcdhError::PointAtInfinity)?;
+
+        let (_, x_bytes) = x.to_bytes_be();
+        let mut bytes = vec![0u8; curve.field_length() - x_bytes.len()];
+        bytes.extend_from_slice(&x_bytes);
+
+        Ok(SharedSecret { bytes })
     }
-}
\ No newline at end of file
+}
+
+impl PublicKey {
+    ///from_point() wraps a peer's point after validate_public_point()
+    pub fn from_point(domain: impl Into<DomainParameters>, point: Point) -> Result<Self, EcdhError> {
+        let domain = domain.into();
+        validate_public_point(&domain, &point)?;
+
+        Ok(Self { domain, point })
+    }
+
+    ///from_sec1_bytes() decodes a compressed or uncompressed SEC 1 point and validates it
+    pub fn from_sec1_bytes(domain: impl Into<DomainParameters>, bytes: &[u8]) -> Result<Self, EcdhError> {
+        let domain = domain.into();
+        let point = Point::from_sec1_bytes(domain.curve(), bytes)?;
+
+        Self::from_point(domain, point)
+    }
+
+    pub fn to_sec1_bytes(&self, compressed: bool) -> Vec<u8> {
+        self.point.to_sec1_bytes(self.domain.curve(), compressed)
+    }
+
+    pub fn point(&self) -> &Point {
+        &self.point
+    }
+
+    pub fn domain(&self) -> &DomainParameters {
+        &self.domain
+    }
+}
+
+impl SharedSecret {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::elliptic_curve_primitives::{EC, NamedCurve};
+
+    // E: y^2 = x^3 + 2x + 2 mod 17, and generator point G = (5,1) of order 19
+    fn small_domain() -> DomainParameters {
+        let e = EC::new(BigInt::from(2), BigInt::from(2), BigInt::from(17));
+        let g = Point::Affine(BigInt::from(5), BigInt::from(1));
+        DomainParameters::new(e, g, BigInt::from(19), BigInt::from(1))
+    }
+
+    #[test]
+    fn test_small_curve_example() {
+        //Alice chooses secret alpha = 3 and Bob beta = 11
+        let alice = EphemeralSecret::from_scalar(small_domain(), &BigInt::from(3));
+        let bob = EphemeralSecret::from_scalar(small_domain(), &BigInt::from(11));
+
+        //alpha*G = (10,6) and beta*G = (13,10) are exchanged
+        let alice_public = alice.public_key();
+        let bob_public = bob.public_key();
+        assert_eq!(alice_public.point(), &Point::Affine(BigInt::from(10), BigInt::from(6)));
+        assert_eq!(bob_public.point(), &Point::Affine(BigInt::from(13), BigInt::from(10)));
+
+        //alpha*(beta*G) = beta*(alpha*G) = 33*G = 14*G = (9,1)
+        let alice_shared = alice.diffie_hellman(&bob_public).unwrap();
+        let bob_shared = bob.diffie_hellman(&alice_public).unwrap();
+        assert_eq!(alice_shared.as_bytes(), &[9]);
+        assert_eq!(bob_shared.as_bytes(), &[9]);
+    }
+
+    #[test]
+    fn test_named_curve_exchange() {
+        let mut rng = rand::rng();
+
+        for curve in NamedCurve::ALL {
+            let alice = EphemeralSecret::random(curve, &mut rng);
+            let bob = EphemeralSecret::random(curve, &mut rng);
+
+            //public keys travel as compressed SEC 1 points
+            let alice_public = PublicKey::from_sec1_bytes(curve, &alice.public_key().to_sec1_bytes(true)).unwrap();
+            let bob_public = PublicKey::from_sec1_bytes(curve, &bob.public_key().to_sec1_bytes(true)).unwrap();
+
+            let alice_shared = alice.diffie_hellman(&bob_public).unwrap();
+            let bob_shared = bob.diffie_hellman(&alice_public).unwrap();
+
+            assert_eq!(alice_shared.as_bytes(), bob_shared.as_bytes());
+            assert_eq!(alice_shared.as_bytes().len(), curve.domain_parameters().curve().field_length());
+        }
+    }
+
+    #[test]
+    fn test_p256_known_answer() {
+        //shared secret for d = 0xc0ffee and d' = 0xdecaf, from Python cryptography
+        let alice = EphemeralSecret::from_scalar(NamedCurve::P256.into(), &BigInt::from(0xc0ffee));
+        let bob = EphemeralSecret::from_scalar(NamedCurve::P256.into(), &BigInt::from(0xdecaf));
+
+        let shared = alice.diffie_hellman(&bob.public_key()).unwrap();
+        assert_eq!(hex::encode(shared.as_bytes()), "36ea61c307acd70ed51912c13851dd1236260209c4d6f15de2857d67dbfaf1b4");
+    }
+
+    #[test]
+    fn test_validate_public_point() {
+        let domain = NamedCurve::P256.domain_parameters();
+        let p = domain.curve().p().clone();
+        let g = domain.generator().clone();
+        let (g_x, g_y) = (g.x().unwrap().clone(), g.y().unwrap().clone());
+
+        assert_eq!(validate_public_point(&domain, &g), Ok(()));
+        assert_eq!(validate_public_point(&domain, &Point::Identity), Err(EcdhError::PointAtInfinity));
+
+        //(x + p, y) satisfies the curve equation mod p but isn't a canonical point
+        let unreduced = Point::Affine(&g_x + &p, g_y.clone());
+        assert_eq!(validate_public_point(&domain, &unreduced), Err(EcdhError::CoordinateOutOfRange));
+        let negative = Point::Affine(g_x.clone(), &g_y - &p);
+        assert_eq!(validate_public_point(&domain, &negative), Err(EcdhError::CoordinateOutOfRange));
+
+        //invalid-curve point: lies on y^2 = x^3 - 3x + b' for some other b'
+        let off_curve = Point::Affine(g_x, g_y + BigInt::from(1));
+        assert_eq!(validate_public_point(&domain, &off_curve), Err(EcdhError::PointNotOnCurve));
+        assert_eq!(PublicKey::from_point(NamedCurve::P256, off_curve).unwrap_err(), EcdhError::PointNotOnCurve);
+        assert_eq!(PublicKey::from_point(NamedCurve::P256, Point::Identity).unwrap_err(), EcdhError::PointAtInfinity);
+
+        assert_eq!(PublicKey::from_sec1_bytes(NamedCurve::P256, &[0x00]).unwrap_err(), EcdhError::PointAtInfinity);
+        assert_eq!(PublicKey::from_sec1_bytes(NamedCurve::P256, &[0x02; 3]).unwrap_err(), EcdhError::InvalidEncoding);
+    }
+
+    #[test]
+    fn test_small_subgroup_point_rejected() {
+        // E: y^2 = x^3 + x + 5 mod 23 has 22 points, G = (11,6) of order 11 and cofactor 2
+        let e = EC::new(BigInt::from(1), BigInt::from(5), BigInt::from(23));
+        let g = Point::Affine(BigInt::from(11), BigInt::from(6));
+        let domain = DomainParameters::new(e, g, BigInt::from(11), BigInt::from(2));
+
+        //(16,0) has order 2 and (3,9) has order 22, both on E but outside <G>
+        let order_two = Point::Affine(BigInt::from(16), BigInt::from(0));
+        let order_22 = Point::Affine(BigInt::from(3), BigInt::from(9));
+        assert_eq!(validate_public_point(&domain, &order_two), Err(EcdhError::PointNotInSubgroup));
+        assert_eq!(validate_public_point(&domain, &order_22), Err(EcdhError::PointNotInSubgroup));
+        assert_eq!(PublicKey::from_point(domain.clone(), order_two).unwrap_err(), EcdhError::PointNotInSubgroup);
+
+        let secret = EphemeralSecret::from_scalar(domain.clone(), &BigInt::from(4));
+        let peer = EphemeralSecret::from_scalar(domain, &BigInt::from(7)).public_key();
+        assert!(secret.diffie_hellman(&peer).is_ok());
+    }
+
+    #[test]
+    fn test_curve_mismatch() {
+        let mut rng = rand::rng();
+        let secret = EphemeralSecret::random(NamedCurve::P256, &mut rng);
+        let peer = EphemeralSecret::random(NamedCurve::Secp256k1, &mut rng).public_key();
+
+        assert_eq!(secret.diffie_hellman(&peer).unwrap_err(), EcdhError::CurveMismatch);
+    }
+}
@@ -1,143 +1,536 @@
-use crate::elliptic_curve_primitives as elliptic_curve_helper;
-use crate::number_theory_primitives as number_theory_helper;
-use num_bigint::BigInt;
-use sha2::{Sha256, Digest};
-use std::ops::{Rem};
-
-fn main(message:BigInt) {
-    // example with
-    // E: y^2 = x^3 + 2x + 2 mod 17, and generator point G = (5,11) 
-
-    //instantiate a basic elliptic curve and define a pre-computed generator point
-    let modulus = BigInt::from(17);
-    let a = BigInt::from(2);
-    let b = BigInt::from(2);
-
-    let elliptic_curve = elliptic_curve_helper::EC {a,b, p:modulus.clone()};
-    let generator = EcGroupElement { point:(BigInt::from(5), BigInt::from(1)) };
-
-    //define private discrete logs q (private key) and k (randomness parameter) for Q = q*G and R = k*G resp.
-    let q = DiscreteLog { dl: BigInt::from(5) };
-    let k = DiscreteLog { dl: BigInt::from(6) };
-
-    //hash message to be signed. Returns integer
-    let h: BigInt = hash(&message);
-
-    //generate public key Q=q*G from private key q
-    let point_Q = EcGroupElement::new(q.dl.clone(), generator.point.clone(), &elliptic_curve);
-    //generate randomness point R=k*G and only take the x coordinate: r = R.x
-    let point_R = EcGroupElement::new(k.dl.clone(), generator.point.clone(), &elliptic_curve);
-    let r = point_R.return_x_value();
-
-    //compute s = (h+rp)/k
-    let s:BigInt = compute_public_parameter_s(&h, &r, &q.dl, &k.dl, &modulus);
-    
-    //Prover sends (Q, h, r, s) to the Verifier.
-    let package = ProverPackage {
-        Q: point_Q,
-        hashed_message: h,
-        r,
-        s,
-        elliptic_curve,
-        modulus,
-        generator
-    };
-
-    let verifier = Verifier {
-        package
-    };
-
-    let result = verifier.verify_signature();
-    if result {
-        println!("Signature is valid")
-    } else {
-        println!("Signature is invalid")
-    }
-
-
-}
-
-
-//Given generator G use constructions below to both compute
-// 1. Public key Q using private key q chosen by the prover. Computed as Q = q*G
-// 2. pseudo-randomness parameter R given random field element k. Computed as R = k*G
-struct DiscreteLog {
-    dl: BigInt
-}
-
-struct EcGroupElement {
-    point: (BigInt, BigInt)
-}
-
-impl EcGroupElement {
-    fn new(dl: BigInt, generator:(BigInt, BigInt), ec: &elliptic_curve_helper::EC) -> Self {
-        //Given the provers chosen private key, the elliptic curve and the ec's generator point, compute the public key Q
-        let point = elliptic_curve_helper::EC::point_n_addition(ec, generator, dl);
-
-        Self { point }
-    }
-
-    fn return_x_value(&self) -> BigInt {
-        self.point.0.clone()
-    }
-    
-}
-
-fn compute_public_parameter_s(h:&BigInt, r:&BigInt, q:&BigInt, k:&BigInt, modulus:&BigInt) -> BigInt {
-    // function computes s = (h + r*p) / k, 
-    // which sets up verification step R = s^-1 (h*G + r*Q)
-    let k_inv = number_theory_helper::multiplicative_inverse(k.clone(), modulus.clone());
-    let numerator:BigInt = h + r*q;
-    (k_inv*numerator).rem(modulus)
-}
-
-
-
-
-fn hash(m: &BigInt) -> BigInt {
-    //1. takes integer message m and converts to binary string
-    //
-    //2. instantiate sha256 hasher and pass in binary string
-    // 
-    //3. convert binary string output to integer hash
-    let message = format!("b{}", m);
-    let mut hasher = Sha256::new();
-    hasher.update(&message);
-    let hashed_m = hasher.finalize();
-    BigInt::from_bytes_be(num_bigint::Sign::Plus, &hashed_m)
-}
-
-
-
-struct ProverPackage {
-    Q: EcGroupElement,
-    hashed_message: BigInt,
-    r: BigInt,
-    s: BigInt,
-    elliptic_curve: elliptic_curve_helper::EC,
-    modulus: BigInt,
-    generator: EcGroupElement
-}
-
-struct Verifier {
-    package: ProverPackage
-}
-
-impl Verifier {
-    fn verify_signature(self) -> bool {
-        let s_inv: BigInt = number_theory_helper::multiplicative_inverse(self.package.s, self.package.modulus);
-        let hG: (BigInt, BigInt) = EcGroupElement::new(self.package.hashed_message, self.package.generator.point, &self.package.elliptic_curve).point;
-        let rP: (BigInt, BigInt) = EcGroupElement::new(self.package.r.clone(), self.package.Q.point, &self.package.elliptic_curve).point;
-        let intermediate_point = self.package.elliptic_curve.point_addition_unique(&hG, &rP);
-        let result = self.package.elliptic_curve.point_n_addition(intermediate_point, s_inv);
-
-        let result_x = result.0;
-
-
-        if result_x == self.package.r {
-            return true
-        }
-
-        false
-    }
-}
\ No newline at end of file
+use crate::elliptic_curve_primitives as elliptic_curve_helper;
+use crate::number_theory_primitives as number_theory_helper;
+use crate::rfc6979;
+use crate::rng;
+use num_bigint::BigInt;
+use rand::CryptoRng;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use sha2::digest::core_api::BlockSizeUser;
+use std::fmt;
+use std::ops::{Rem};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcdsaError {
+    InvalidPrivateKey,
+    InvalidPublicKey,
+    InvalidDigestLength,
+}
+
+impl fmt::Display for EcdsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcdsaError::InvalidPrivateKey => write!(f, "private key must satisfy 1 <= d <= n - 1"),
+            EcdsaError::InvalidPublicKey => write!(f, "public key is not an affine point on the curve"),
+            EcdsaError::InvalidDigestLength => write!(f, "digest length does not match the hash function"),
+        }
+    }
+}
+
+impl std::error::Error for EcdsaError {}
+
+///ECDSA private key
+///
+/// fields:
+///     - d = private discrete log, 1 <= d <= n - 1
+///     - verifying_key = domain parameters and public point Q = d*G
+#[derive(Clone, PartialEq, Eq)]
+pub struct SigningKey {
+    d: BigInt,
+    verifying_key: VerifyingKey
+}
+
+///ECDSA public key: public point Q = d*G together with the curve domain parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey {
+    domain: elliptic_curve_helper::DomainParameters,
+    point: elliptic_curve_helper::Point
+}
+
+///ECDSA signature (r, s)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    r: BigInt,
+    s: BigInt
+}
+
+//only the public key is printed so the discrete log can't leak through logs
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("verifying_key", &self.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigningKey {
+    ///new() takes the domain parameters (or a NamedCurve) and the private key d, and
+    ///computes the public point Q = d*G
+    pub fn new(domain: impl Into<elliptic_curve_helper::DomainParameters>, d: BigInt) -> Result<Self, EcdsaError> {
+        let domain = domain.into();
+        if d < BigInt::from(1) || &d >= domain.order() {
+            return Err(EcdsaError::InvalidPrivateKey)
+        };
+
+        let point = domain.curve().point_n_addition(domain.generator().clone(), d.clone())
+            .expect("DomainParameters::new checked the field size");
+        let verifying_key = VerifyingKey { domain, point };
+
+        Ok(Self { d, verifying_key })
+    }
+
+    ///random() draws d uniformly from [1, n-1]
+    pub fn random<R: CryptoRng + ?Sized>(domain: impl Into<elliptic_curve_helper::DomainParameters>, rng: &mut R) -> Self {
+        let domain = domain.into();
+        let d = random_scalar(domain.order(), rng);
+
+        Self::new(domain, d).unwrap()
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    ///sign() hashes the message with the curve's default hash and signs it with the
+    ///deterministic nonce k of RFC 6979, so the same key and message always give the
+    ///same signature. the hash is SHA-256 for orders up to 256 bits (P-256, secp256k1),
+    ///SHA-384 for P-384 and SHA-512 for P-521
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        match DefaultHash::for_order(self.verifying_key.domain.order()) {
+            DefaultHash::Sha256 => self.sign_digest(Sha256::new_with_prefix(message)),
+            DefaultHash::Sha384 => self.sign_digest(Sha384::new_with_prefix(message)),
+            DefaultHash::Sha512 => self.sign_digest(Sha512::new_with_prefix(message)),
+        }
+    }
+
+    ///sign_digest() signs a hasher the caller has fed with update(), so large messages
+    ///can be streamed instead of held in memory. D can be any hash (SHA-224, SHA-512/256,
+    ///SHA3-256, ...) and is also the HMAC hash of the RFC 6979 nonce derivation
+    pub fn sign_digest<D: Digest + BlockSizeUser>(&self, digest: D) -> Signature {
+        self.sign_with_additional_data::<D>(&digest.finalize(), &[])
+    }
+
+    ///sign_prehashed() signs a digest the caller has already computed with D
+    pub fn sign_prehashed<D: Digest + BlockSizeUser>(&self, digest: &[u8]) -> Result<Signature, EcdsaError> {
+        if digest.len() != <D as Digest>::output_size() {
+            return Err(EcdsaError::InvalidDigestLength)
+        }
+
+        Ok(self.sign_with_additional_data::<D>(digest, &[]))
+    }
+
+    ///sign_hedged() mixes fresh randomness from rng into the RFC 6979 nonce derivation
+    ///(section 3.6). signatures are randomised, but k stays secret even if rng is weak
+    pub fn sign_hedged<R: CryptoRng + ?Sized>(&self, message: &[u8], rng: &mut R) -> Signature {
+        match DefaultHash::for_order(self.verifying_key.domain.order()) {
+            DefaultHash::Sha256 => self.sign_digest_hedged(Sha256::new_with_prefix(message), rng),
+            DefaultHash::Sha384 => self.sign_digest_hedged(Sha384::new_with_prefix(message), rng),
+            DefaultHash::Sha512 => self.sign_digest_hedged(Sha512::new_with_prefix(message), rng),
+        }
+    }
+
+    ///sign_digest_hedged() is sign_hedged() over a hasher the caller has fed with update()
+    pub fn sign_digest_hedged<D: Digest + BlockSizeUser, R: CryptoRng + ?Sized>(&self, digest: D, rng: &mut R) -> Signature {
+        let mut entropy = vec![0u8; self.verifying_key.domain.order().bits().div_ceil(8) as usize];
+        rng.fill_bytes(&mut entropy);
+
+        self.sign_with_additional_data::<D>(&digest.finalize(), &entropy)
+    }
+
+    //draws nonces from the RFC 6979 generator until one gives r and s both non-zero.
+    //h1 is the message digest, truncated to bitlen(n) bits to give the hash h
+    fn sign_with_additional_data<D: Digest + BlockSizeUser>(&self, h1: &[u8], additional_data: &[u8]) -> Signature {
+        let order = self.verifying_key.domain.order();
+        let h: BigInt = rfc6979::bits2int(h1, order.bits());
+        let mut nonces = rfc6979::NonceGenerator::<D>::new(order, &self.d, h1, additional_data);
+
+        loop {
+            let k = nonces.next_nonce();
+
+            if let Some(signature) = self.sign_with_nonce(&h, &k) {
+                return signature
+            }
+        }
+    }
+
+    //signs hash h with nonce k, all scalar arithmetic mod the group order n:
+    // 1. R = k*G and r = R.x mod n
+    // 2. s = k^-1 (h + r*d) mod n
+    //returns None if R = O or r or s is zero, in which case a new k is needed
+    fn sign_with_nonce(&self, h: &BigInt, k: &BigInt) -> Option<Signature> {
+        let domain = &self.verifying_key.domain;
+        let n = domain.order();
+
+        //generate randomness point R=k*G and only take the x coordinate: r = R.x mod n
+        let point_r = domain.curve().point_n_addition(domain.generator().clone(), k.clone())
+            .expect("DomainParameters::new checked the field size");
+        let r = point_r.x()?.rem(n);
+
+        let s = compute_public_parameter_s(h, &r, &self.d, k, n);
+
+        if r == BigInt::ZERO || s == BigInt::ZERO {
+            return None
+        }
+
+        Some(Signature { r, s })
+    }
+}
+
+impl VerifyingKey {
+    ///from_sec1_bytes() decodes a compressed or uncompressed SEC 1 public point Q,
+    ///which must lie on the curve and not be the point at infinity
+    pub fn from_sec1_bytes(domain: impl Into<elliptic_curve_helper::DomainParameters>, bytes: &[u8]) -> Result<Self, EcdsaError> {
+        let domain = domain.into();
+        let point = elliptic_curve_helper::Point::from_sec1_bytes(domain.curve(), bytes)
+            .map_err(|_| EcdsaError::InvalidPublicKey)?;
+
+        if point.is_identity() {
+            return Err(EcdsaError::InvalidPublicKey)
+        }
+
+        Ok(Self { domain, point })
+    }
+
+    pub fn to_sec1_bytes(&self, compressed: bool) -> Vec<u8> {
+        self.point.to_sec1_bytes(self.domain.curve(), compressed)
+    }
+
+    pub fn point(&self) -> &elliptic_curve_helper::Point {
+        &self.point
+    }
+
+    pub fn domain(&self) -> &elliptic_curve_helper::DomainParameters {
+        &self.domain
+    }
+
+    ///verify() hashes the message with the curve's default hash, the same one sign()
+    ///uses, and checks the signature over it
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        match DefaultHash::for_order(self.domain.order()) {
+            DefaultHash::Sha256 => self.verify_digest(Sha256::new_with_prefix(message), signature),
+            DefaultHash::Sha384 => self.verify_digest(Sha384::new_with_prefix(message), signature),
+            DefaultHash::Sha512 => self.verify_digest(Sha512::new_with_prefix(message), signature),
+        }
+    }
+
+    ///verify_digest() checks a signature over a hasher the caller has fed with update()
+    pub fn verify_digest<D: Digest>(&self, digest: D, signature: &Signature) -> bool {
+        self.verify_prehashed::<D>(&digest.finalize(), signature)
+    }
+
+    ///verify_prehashed() checks 1 <= r, s <= n - 1 and that R' = u_1*G + u_2*Q
+    ///satisfies R'.x = r (mod n), where h is the digest truncated to bitlen(n) bits
+    /// 1. u_1 = h * s^-1 mod n
+    /// 2. u_2 = r * s^-1 mod n
+    ///
+    ///a digest that isn't exactly one D output never verifies
+    pub fn verify_prehashed<D: Digest>(&self, digest: &[u8], signature: &Signature) -> bool {
+        let curve = self.domain.curve();
+        let n = self.domain.order();
+        let (r, s) = (&signature.r, &signature.s);
+
+        if digest.len() != <D as Digest>::output_size() {
+            return false
+        }
+        if r < &BigInt::from(1) || r >= n || s < &BigInt::from(1) || s >= n {
+            return false
+        }
+
+        let h: BigInt = rfc6979::bits2int(digest, n.bits());
+        let s_inv: BigInt = number_theory_helper::multiplicative_inverse(s.clone(), n.clone());
+        let u_1: BigInt = (h * &s_inv).rem(n);
+        let u_2: BigInt = (r * &s_inv).rem(n);
+
+        //u_1 and u_2 are public, so the faster variable-time multiplication is fine
+        let u_1_g = curve.point_n_addition_vartime(self.domain.generator().clone(), u_1);
+        let u_2_q = curve.point_n_addition_vartime(self.point.clone(), u_2);
+        let result = curve.point_addition(&u_1_g, &u_2_q);
+
+        //R' = O can't match any r
+        match result.x() {
+            Some(x) => &x.rem(n) == r,
+            None => false
+        }
+    }
+}
+
+impl Signature {
+    pub fn new(r: BigInt, s: BigInt) -> Self {
+        Self { r, s }
+    }
+
+    pub fn r(&self) -> &BigInt {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigInt {
+        &self.s
+    }
+}
+
+fn compute_public_parameter_s(h:&BigInt, r:&BigInt, d:&BigInt, k:&BigInt, order:&BigInt) -> BigInt {
+    // function computes s = (h + r*d) / k mod n,
+    // which sets up verification step R = s^-1 (h*G + r*Q)
+    // k is secret, so it is inverted in fixed time as k^(n-2) mod n
+    let k_inv = elliptic_curve_helper::invert_mod_prime(k, order)
+        .expect("DomainParameters::new checked the size of n");
+    let numerator:BigInt = h + r*d;
+    (k_inv*numerator).rem(order)
+}
+
+//hash used by sign() and verify(), the SHA-2 function matching the security strength
+//of the group order: SHA-256 up to 256-bit n (P-256, secp256k1 and smaller curves),
+//SHA-384 for P-384 and SHA-512 for P-521
+enum DefaultHash {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DefaultHash {
+    fn for_order(order: &BigInt) -> DefaultHash {
+        match order.bits() {
+            0..=256 => DefaultHash::Sha256,
+            257..=384 => DefaultHash::Sha384,
+            _ => DefaultHash::Sha512,
+        }
+    }
+}
+
+//uniform scalar in [1, n-1]
+fn random_scalar<R: CryptoRng + ?Sized>(order: &BigInt, rng: &mut R) -> BigInt {
+    let (_, bound) = (order - BigInt::from(1)).into_parts();
+
+    BigInt::from(rng::generate_random_below(&bound, rng)) + BigInt::from(1)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::elliptic_curve_primitives::{DomainParameters, EC, NamedCurve, Point};
+    use sha2::Sha512_256;
+    use sha3::Sha3_256;
+
+    fn from_hex(hex: &str) -> BigInt {
+        BigInt::parse_bytes(hex.as_bytes(), 16).unwrap()
+    }
+
+    fn p256() -> DomainParameters {
+        NamedCurve::P256.domain_parameters()
+    }
+
+    //NIST P-192 domain parameters (FIPS 186-4 D.1.2.1). n is shorter than a SHA-256
+    //digest, so signatures over it exercise hash truncation
+    fn p192() -> DomainParameters {
+        let p = from_hex("fffffffffffffffffffffffffffffffeffffffffffffffff");
+        let a = &p - BigInt::from(3);
+        let b = from_hex("64210519e59c80e70fa7e9ab72243049feb8deecc146b9b1");
+        let generator = Point::Affine(
+            from_hex("188da80eb03090f67cbf20eb43a18800f4ff0afd82ff1012"),
+            from_hex("07192b95ffc8da78631011ed6b24cdd573f977a11e794811")
+        );
+        let order = from_hex("ffffffffffffffffffffffff99def836146bc9b1b4d22831");
+
+        DomainParameters::new(EC::new(a, b, p), generator, order, BigInt::from(1))
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = rand::rng();
+
+        for domain in [p256(), p192()] {
+            let signing_key = SigningKey::random(domain, &mut rng);
+            let verifying_key = signing_key.verifying_key();
+
+            let signature = signing_key.sign(b"ECDSA test message");
+            assert!(verifying_key.verify(b"ECDSA test message", &signature));
+
+            //plain signatures are deterministic, hedged ones are randomised
+            assert_eq!(signing_key.sign(b"ECDSA test message"), signature);
+            let hedged = signing_key.sign_hedged(b"ECDSA test message", &mut rng);
+            assert_ne!(hedged, signature);
+            assert_ne!(signing_key.sign_hedged(b"ECDSA test message", &mut rng), hedged);
+            assert!(verifying_key.verify(b"ECDSA test message", &hedged));
+
+            //other message or tampered signature
+            assert!(!verifying_key.verify(b"other message", &signature));
+            let tampered = Signature::new(signature.r().clone(), signature.s() + BigInt::from(1));
+            assert!(!verifying_key.verify(b"ECDSA test message", &tampered));
+            let zero = Signature::new(BigInt::ZERO, signature.s().clone());
+            assert!(!verifying_key.verify(b"ECDSA test message", &zero));
+
+            //r and s must be reduced mod n
+            let n = verifying_key.domain().order();
+            let unreduced = Signature::new(signature.r() + n, signature.s().clone());
+            assert!(!verifying_key.verify(b"ECDSA test message", &unreduced));
+        }
+    }
+
+    #[test]
+    fn test_named_curves() {
+        let mut rng = rand::rng();
+
+        for curve in NamedCurve::ALL {
+            let signing_key = SigningKey::random(curve, &mut rng);
+            let signature = signing_key.sign(b"ECDSA test message");
+
+            assert!(signing_key.verifying_key().verify(b"ECDSA test message", &signature));
+            assert!(!signing_key.verifying_key().verify(b"other message", &signature));
+        }
+    }
+
+    #[test]
+    fn test_rfc6979_vectors() {
+        //RFC 6979 A.2.5 (P-256) and A.2.3 (P-192) with SHA-256:
+        //(curve, x, Ux, Uy, message, k, r, s)
+        let vectors = [
+            (
+                p256(),
+                "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+                "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+                "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299",
+                "sample",
+                "a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60",
+                "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716",
+                "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"
+            ),
+            (
+                p256(),
+                "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+                "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+                "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299",
+                "test",
+                "d16b6ae827f17175e040871a1c7ec3500192c4c92677336ec2537acaee0008e0",
+                "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367",
+                "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083"
+            ),
+            (
+                p192(),
+                "6fab034934e4c0fc9ae67f5b5659a9d7d1fefd187ee09fd4",
+                "ac2c77f529f91689fea0ea5efec7f210d8eea0b9e047ed56",
+                "3bc723e57670bd4887ebc732c523063d0a7c957bc97c1c43",
+                "sample",
+                "32b1b6d7d42a05cb449065727a84804fb1a3e34d8f261496",
+                "4b0b8ce98a92866a2820e20aa6b75b56382e0f9bfd5ecb55",
+                "ccdb006926ea9565cbadc840829d8c384e06de1f1e381b85"
+            ),
+        ];
+
+        for (domain, x, ux, uy, message, k, r, s) in vectors {
+            let signing_key = SigningKey::new(domain, from_hex(x)).unwrap();
+            let verifying_key = signing_key.verifying_key();
+            assert_eq!(verifying_key.point(), &Point::Affine(from_hex(ux), from_hex(uy)));
+
+            let expected = Signature::new(from_hex(r), from_hex(s));
+            let h = rfc6979::bits2int(&Sha256::digest(message), verifying_key.domain().order().bits());
+            assert_eq!(signing_key.sign_with_nonce(&h, &from_hex(k)), Some(expected.clone()));
+
+            //sign() derives the same k itself
+            assert_eq!(signing_key.sign(message.as_bytes()), expected);
+
+            assert!(verifying_key.verify(message.as_bytes(), &expected));
+        }
+    }
+
+    #[test]
+    fn test_sec1_public_key() {
+        let mut rng = rand::rng();
+
+        for curve in NamedCurve::ALL {
+            let signing_key = SigningKey::random(curve, &mut rng);
+            let signature = signing_key.sign(b"sample");
+
+            for compressed in [true, false] {
+                let bytes = signing_key.verifying_key().to_sec1_bytes(compressed);
+                let verifying_key = VerifyingKey::from_sec1_bytes(curve, &bytes).unwrap();
+
+                assert_eq!(&verifying_key, signing_key.verifying_key());
+                assert!(verifying_key.verify(b"sample", &signature));
+            }
+        }
+
+        assert_eq!(VerifyingKey::from_sec1_bytes(NamedCurve::P256, &[0x00]).unwrap_err(), EcdsaError::InvalidPublicKey);
+        assert_eq!(VerifyingKey::from_sec1_bytes(NamedCurve::P256, &[0x04; 65]).unwrap_err(), EcdsaError::InvalidPublicKey);
+    }
+
+    #[test]
+    fn test_digest_and_prehashed() {
+        let signing_key = SigningKey::new(p256(), from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")).unwrap();
+        let verifying_key = signing_key.verifying_key();
+        let signature = signing_key.sign(b"sample");
+
+        //a message fed to the hasher in pieces signs the same as the whole message
+        let mut digest = Sha256::new();
+        digest.update(b"sam");
+        digest.update(b"ple");
+        assert_eq!(signing_key.sign_digest(digest.clone()), signature);
+        assert!(verifying_key.verify_digest(digest, &signature));
+
+        let prehashed = Sha256::digest(b"sample");
+        assert_eq!(signing_key.sign_prehashed::<Sha256>(&prehashed), Ok(signature.clone()));
+        assert!(verifying_key.verify_prehashed::<Sha256>(&prehashed, &signature));
+
+        //the digest must be exactly one SHA-256 output
+        assert_eq!(signing_key.sign_prehashed::<Sha256>(&prehashed[..31]), Err(EcdsaError::InvalidDigestLength));
+        assert!(!verifying_key.verify_prehashed::<Sha256>(&prehashed[..31], &signature));
+        assert!(!verifying_key.verify_prehashed::<Sha256>(&Sha256::digest(b"other message"), &signature));
+    }
+
+    #[test]
+    fn test_hash_selection() {
+        //RFC 6979 A.2.5 - A.2.7 over "sample": P-384 and P-521 default to SHA-384 and
+        //SHA-512, other hashes are picked with sign_digest()
+        let p384 = SigningKey::new(NamedCurve::P384, from_hex("6b9d3dad2e1b8c1c05b19875b6659f4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825f872c9ea60d2edf5")).unwrap();
+        let expected = Signature::new(
+            from_hex("94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe46"),
+            from_hex("99ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8")
+        );
+        assert_eq!(p384.sign(b"sample"), expected);
+        assert_eq!(p384.sign_digest(Sha384::new_with_prefix(b"sample")), expected);
+        assert!(p384.verifying_key().verify(b"sample", &expected));
+        assert!(!p384.verifying_key().verify_digest(Sha256::new_with_prefix(b"sample"), &expected));
+
+        let p521 = SigningKey::new(NamedCurve::P521, from_hex("0fad06daa62ba3b25d2fb40133da757205de67f5bb0018fee8c86e1b68c7e75caa896eb32f1f47c70855836a6d16fcc1466f6d8fbec67db89ec0c08b0e996b83538")).unwrap();
+        let expected = Signature::new(
+            from_hex("c328fafcbd79dd77850370c46325d987cb525569fb63c5d3bc53950e6d4c5f174e25a1ee9017b5d450606add152b534931d7d4e8455cc91f9b15bf05ec36e377fa"),
+            from_hex("617cce7cf5064806c467f678d3b4080d6f1cc50af26ca209417308281b68af282623eaa63e5b5c0723d8b8c37ff0777b1a20f8ccb1dccc43997f1ee0e44da4a67a")
+        );
+        assert_eq!(p521.sign(b"sample"), expected);
+        assert!(p521.verifying_key().verify(b"sample", &expected));
+
+        //P-256 with SHA-512 (RFC 6979) and SHA3-256 (deterministic signature from Python cryptography)
+        let p256_key = SigningKey::new(p256(), from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")).unwrap();
+        let sha512 = Signature::new(
+            from_hex("8496a60b5e9b47c825488827e0495b0e3fa109ec4568fd3f8d1097678eb97f00"),
+            from_hex("2362ab1adbe2b8adf9cb9edab740ea6049c028114f2460f96554f61fae3302fe")
+        );
+        let sha3_256 = Signature::new(
+            from_hex("8fedfdf147364db550f840aebfe7c26df77a9ab56c9aea20ac33e45e1aedd7ac"),
+            from_hex("3a5bd6183374df2517910db14e0a9cc4666ae679c4d1ebb89242fb3062db6068")
+        );
+        assert_eq!(p256_key.sign_digest(Sha512::new_with_prefix(b"sample")), sha512);
+        assert_eq!(p256_key.sign_prehashed::<Sha3_256>(&Sha3_256::digest(b"sample")), Ok(sha3_256.clone()));
+        assert!(p256_key.verifying_key().verify_digest(Sha3_256::new_with_prefix(b"sample"), &sha3_256));
+        assert!(!p256_key.verifying_key().verify(b"sample", &sha3_256));
+
+        //hedged signatures with an explicit hash
+        let mut rng = rand::rng();
+        let hedged = p256_key.sign_digest_hedged(Sha512_256::new_with_prefix(b"sample"), &mut rng);
+        assert!(p256_key.verifying_key().verify_digest(Sha512_256::new_with_prefix(b"sample"), &hedged));
+        assert_eq!(p256_key.sign_prehashed::<Sha384>(&Sha256::digest(b"sample")), Err(EcdsaError::InvalidDigestLength));
+    }
+
+    #[test]
+    fn test_private_key_range() {
+        let domain = p256();
+        let order = domain.order().clone();
+
+        assert_eq!(SigningKey::new(domain.clone(), BigInt::ZERO).unwrap_err(), EcdsaError::InvalidPrivateKey);
+        assert_eq!(SigningKey::new(domain.clone(), order).unwrap_err(), EcdsaError::InvalidPrivateKey);
+
+        //d = 1 gives Q = G
+        let signing_key = SigningKey::new(domain.clone(), BigInt::from(1)).unwrap();
+        assert_eq!(signing_key.verifying_key().point(), domain.generator());
+    }
+}
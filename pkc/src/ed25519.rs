@@ -0,0 +1,539 @@
+use crypto_bigint::{Encoding, NonZero, Odd, U256, U512};
+use crypto_bigint::modular::{MontyForm, MontyParams};
+use rand::CryptoRng;
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use std::fmt;
+use std::sync::OnceLock;
+use zeroize::Zeroize;
+
+
+//Ed25519 signatures (RFC 8032 section 5.1)
+//
+//works on the twisted Edwards curve edwards25519: -x^2 + y^2 = 1 + d x^2 y^2 over
+//GF(2^255 - 19), d = -121665/121666, whose base point B generates a subgroup of
+//prime order L with cofactor 8. points and scalars are 32-byte little-endian strings
+
+type FieldElement = MontyForm<{ U256::LIMBS }>;
+
+///p = 2^255 - 19
+const P: U256 = U256::from_be_hex("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed");
+
+///L = 2^252 + 27742317777372353535851937790883648493, the order of B
+const L: U256 = U256::from_be_hex("1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed");
+
+///encoding of the base point B = (x, 4/5) with x even
+const BASEPOINT_ENCODED: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66
+];
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ed25519Error {
+    InvalidPoint,
+    SmallOrderPoint,
+    NonCanonicalScalar,
+    Verification,
+}
+
+impl fmt::Display for Ed25519Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ed25519Error::InvalidPoint => write!(f, "bytes are not the canonical encoding of a curve point"),
+            Ed25519Error::SmallOrderPoint => write!(f, "point has small order"),
+            Ed25519Error::NonCanonicalScalar => write!(f, "signature scalar S must satisfy S < L"),
+            Ed25519Error::Verification => write!(f, "signature verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for Ed25519Error {}
+
+
+///Ed25519 private key
+///
+/// fields:
+///     - seed = 32-byte secret key from RFC 8032
+///     - scalar = s, the clamped lower half of SHA-512(seed)
+///     - prefix = upper half of SHA-512(seed), used to derive nonces
+///     - verifying_key = encoding of A = [s]B
+///
+///the seed, scalar and prefix are overwritten with zeros on drop
+#[derive(Clone)]
+pub struct Ed25519SigningKey {
+    seed: [u8; 32],
+    scalar: [u8; 32],
+    prefix: [u8; 32],
+    verifying_key: Ed25519VerifyingKey
+}
+
+///Ed25519 public key: the 32-byte encoding of the point A
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519VerifyingKey {
+    bytes: [u8; 32]
+}
+
+///Ed25519 signature R || S
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519Signature {
+    bytes: [u8; 64]
+}
+
+//only the public key is printed so the seed can't leak through logs
+impl fmt::Debug for Ed25519SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ed25519SigningKey")
+            .field("verifying_key", &self.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Ed25519SigningKey {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+        self.scalar.zeroize();
+        self.prefix.zeroize();
+    }
+}
+
+impl Ed25519SigningKey {
+    ///from_bytes() expands a 32-byte secret key (RFC 8032 section 5.1.5)
+    pub fn from_bytes(seed: [u8; 32]) -> Self {
+        let h = Sha512::digest(seed);
+
+        let mut scalar: [u8; 32] = h[..32].try_into().unwrap();
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+        let prefix: [u8; 32] = h[32..].try_into().unwrap();
+
+        let a = EdwardsPoint::basepoint().scalar_multiplication(&scalar);
+        let verifying_key = Ed25519VerifyingKey { bytes: a.encode() };
+
+        Self { seed, scalar, prefix, verifying_key }
+    }
+
+    ///generate() draws a random 32-byte secret key
+    pub fn generate<R: CryptoRng + ?Sized>(rng: &mut R) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+
+        Self::from_bytes(seed)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    pub fn verifying_key(&self) -> &Ed25519VerifyingKey {
+        &self.verifying_key
+    }
+
+    ///sign() is deterministic (RFC 8032 section 5.1.6):
+    /// 1. r = SHA-512(prefix || M) mod L and R = [r]B
+    /// 2. k = SHA-512(R || A || M) mod L
+    /// 3. S = r + k*s mod L
+    pub fn sign(&self, message: &[u8]) -> Ed25519Signature {
+        let r = hash_to_scalar(&[&self.prefix, message]);
+        let r_bytes = EdwardsPoint::basepoint().scalar_multiplication(&r.to_le_bytes()).encode();
+        let k = hash_to_scalar(&[&r_bytes, &self.verifying_key.bytes, message]);
+
+        let params = scalar_params();
+        let s = U256::from_le_bytes(self.scalar).rem(&NonZero::new(L).unwrap());
+        let s = MontyForm::new(&r, params) + MontyForm::new(&k, params) * MontyForm::new(&s, params);
+
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&r_bytes);
+        bytes[32..].copy_from_slice(&s.retrieve().to_le_bytes());
+
+        Ed25519Signature { bytes }
+    }
+}
+
+impl Ed25519VerifyingKey {
+    ///from_bytes() rejects encodings that don't decode to a point, or decode to one
+    ///of the eight points of small order
+    pub fn from_bytes(bytes: [u8; 32]) -> Result<Self, Ed25519Error> {
+        decode_full_order(&bytes)?;
+
+        Ok(Self { bytes })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.bytes
+    }
+
+    ///verify() checks [8][S]B = [8]R + [8][k]A with k = SHA-512(R || A || M) mod L
+    ///(RFC 8032 section 5.1.7), after rejecting S >= L and R of small order
+    pub fn verify(&self, message: &[u8], signature: &Ed25519Signature) -> Result<(), Ed25519Error> {
+        let a = decode_full_order(&self.bytes)?;
+        let r_bytes: [u8; 32] = signature.bytes[..32].try_into().unwrap();
+        let s_bytes: [u8; 32] = signature.bytes[32..].try_into().unwrap();
+
+        if U256::from_le_bytes(s_bytes) >= L {
+            return Err(Ed25519Error::NonCanonicalScalar)
+        }
+        let r = decode_full_order(&r_bytes)?;
+
+        let k = hash_to_scalar(&[&r_bytes, &self.bytes, message]);
+
+        let lhs = EdwardsPoint::basepoint().scalar_multiplication(&s_bytes);
+        let rhs = r.add(&a.scalar_multiplication(&k.to_le_bytes()));
+
+        if !lhs.multiply_by_cofactor().equals(&rhs.multiply_by_cofactor()) {
+            return Err(Ed25519Error::Verification)
+        }
+
+        Ok(())
+    }
+}
+
+impl Ed25519Signature {
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.bytes
+    }
+}
+
+//SHA-512 of the concatenated parts, read little-endian and reduced mod L
+fn hash_to_scalar(parts: &[&[u8]]) -> U256 {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let h = U512::from_le_slice(&hasher.finalize());
+
+    h.rem(&NonZero::new(L.resize()).unwrap()).resize()
+}
+
+//decode a point, rejecting the small order ones
+fn decode_full_order(bytes: &[u8; 32]) -> Result<EdwardsPoint, Ed25519Error> {
+    let point = EdwardsPoint::decode(bytes).ok_or(Ed25519Error::InvalidPoint)?;
+
+    if point.multiply_by_cofactor().is_identity() {
+        return Err(Ed25519Error::SmallOrderPoint)
+    }
+
+    Ok(point)
+}
+
+
+//Montgomery parameters for p and the curve constants, computed once on first use
+struct FieldConstants {
+    params: MontyParams<{ U256::LIMBS }>,
+    d: FieldElement,
+    d_2: FieldElement,
+    sqrt_minus_one: FieldElement
+}
+
+fn field_constants() -> &'static FieldConstants {
+    static CONSTANTS: OnceLock<FieldConstants> = OnceLock::new();
+
+    CONSTANTS.get_or_init(|| {
+        let params = MontyParams::new_vartime(Odd::new(P).unwrap());
+        let element = |x: u64| MontyForm::new(&U256::from(x), params);
+
+        //d = -121665/121666 and sqrt(-1) = 2^((p-1)/4)
+        let d = -element(121665) * invert(&element(121666));
+        let sqrt_minus_one = element(2).pow(&P.wrapping_sub(&U256::ONE).shr_vartime(2));
+
+        FieldConstants { params, d, d_2: d + d, sqrt_minus_one }
+    })
+}
+
+fn field_params() -> MontyParams<{ U256::LIMBS }> {
+    field_constants().params
+}
+
+//Montgomery parameters for L, computed once on first use
+fn scalar_params() -> MontyParams<{ U256::LIMBS }> {
+    static PARAMS: OnceLock<MontyParams<{ U256::LIMBS }>> = OnceLock::new();
+
+    *PARAMS.get_or_init(|| MontyParams::new_vartime(Odd::new(L).unwrap()))
+}
+
+//x^-1 = x^(p-2), and 0 for x = 0
+fn invert(x: &FieldElement) -> FieldElement {
+    x.pow(&P.wrapping_sub(&U256::from(2u64)))
+}
+
+fn is_zero(x: &FieldElement) -> bool {
+    bool::from(x.retrieve().ct_eq(&U256::ZERO))
+}
+
+//point (X : Y : Z : T) in extended coordinates, standing for the affine point
+//(X/Z, Y/Z) with T = XY/Z (Hisil, Wong, Carter and Dawson 2008)
+#[derive(Clone, Copy)]
+struct EdwardsPoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement
+}
+
+impl ConditionallySelectable for EdwardsPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        EdwardsPoint {
+            x: FieldElement::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement::conditional_select(&a.z, &b.z, choice),
+            t: FieldElement::conditional_select(&a.t, &b.t, choice)
+        }
+    }
+}
+
+impl EdwardsPoint {
+    //O = (0, 1)
+    fn identity() -> EdwardsPoint {
+        let params = field_params();
+        EdwardsPoint {
+            x: MontyForm::zero(params),
+            y: MontyForm::one(params),
+            z: MontyForm::one(params),
+            t: MontyForm::zero(params)
+        }
+    }
+
+    fn basepoint() -> EdwardsPoint {
+        EdwardsPoint::decode(&BASEPOINT_ENCODED).unwrap()
+    }
+
+    //decoding from RFC 8032 section 5.1.3: y is the low 255 bits and must be below
+    //p, the top bit is the sign of x, and x is recovered from
+    //x^2 = (y^2 - 1) / (d y^2 + 1)
+    fn decode(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+        let params = field_params();
+        let x_0 = bytes[31] >> 7;
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+
+        let y = U256::from_le_bytes(y_bytes);
+        if y >= P {
+            return None
+        }
+        let y = MontyForm::new(&y, params);
+        let one = MontyForm::one(params);
+
+        //candidate root x = u v^3 (u v^7)^((p-5)/8)
+        let u = y.square() - one;
+        let v = field_constants().d * y.square() + one;
+        let exponent = P.wrapping_sub(&U256::from(5u64)).shr_vartime(3);
+        let v_3 = v.square() * v;
+        let mut x = u * v_3 * (u * v_3 * v_3 * v).pow(&exponent);
+
+        let v_x_2 = v * x.square();
+        if !is_zero(&(v_x_2 - u)) {
+            if !is_zero(&(v_x_2 + u)) {
+                return None
+            }
+            x *= field_constants().sqrt_minus_one;
+        }
+
+        if is_zero(&x) && x_0 == 1 {
+            return None
+        }
+        if (x.retrieve().to_le_bytes()[0] & 1) != x_0 {
+            x = -x;
+        }
+
+        Some(EdwardsPoint { x, y, z: one, t: x * y })
+    }
+
+    //y with the sign of x in the top bit
+    fn encode(&self) -> [u8; 32] {
+        let z_inv = invert(&self.z);
+        let x = (self.x * z_inv).retrieve().to_le_bytes();
+        let mut y = (self.y * z_inv).retrieve().to_le_bytes();
+        y[31] |= (x[0] & 1) << 7;
+        y
+    }
+
+    //unified addition add-2008-hwcd-3 for a = -1. the formula is complete on
+    //edwards25519 (d is not a square), so it also doubles and handles O
+    fn add(&self, other: &EdwardsPoint) -> EdwardsPoint {
+        let k = field_constants().d_2;
+
+        let a = (self.y - self.x) * (other.y - other.x);
+        let b = (self.y + self.x) * (other.y + other.x);
+        let c = self.t * k * other.t;
+        let d = (self.z + self.z) * other.z;
+        let e = b - a;
+        let f = d - c;
+        let g = d + c;
+        let h = b + a;
+
+        EdwardsPoint { x: e * f, y: g * h, z: f * g, t: e * h }
+    }
+
+    //Montgomery ladder over all 256 bits of a little-endian scalar, swapping with
+    //constant-time selection so the timing doesn't depend on the scalar
+    fn scalar_multiplication(&self, scalar: &[u8; 32]) -> EdwardsPoint {
+        let mut r_0 = EdwardsPoint::identity();
+        let mut r_1 = *self;
+        let mut swapped = Choice::from(0);
+
+        for i in (0..256).rev() {
+            let bit = Choice::from((scalar[i / 8] >> (i % 8)) & 1);
+            EdwardsPoint::conditional_swap(&mut r_0, &mut r_1, bit ^ swapped);
+            swapped = bit;
+
+            r_1 = r_0.add(&r_1);
+            r_0 = r_0.add(&r_0);
+        }
+        EdwardsPoint::conditional_swap(&mut r_0, &mut r_1, swapped);
+
+        r_0
+    }
+
+    fn multiply_by_cofactor(&self) -> EdwardsPoint {
+        let p_2 = self.add(self);
+        let p_4 = p_2.add(&p_2);
+        p_4.add(&p_4)
+    }
+
+    fn is_identity(&self) -> bool {
+        is_zero(&self.x) && is_zero(&(self.y - self.z))
+    }
+
+    //(X1 : Y1 : Z1) = (X2 : Y2 : Z2) iff X1 Z2 = X2 Z1 and Y1 Z2 = Y2 Z1
+    fn equals(&self, other: &EdwardsPoint) -> bool {
+        is_zero(&(self.x * other.z - other.x * self.z)) && is_zero(&(self.y * other.z - other.y * self.z))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn from_hex<const N: usize>(hex: &str) -> [u8; N] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        //RFC 8032 section 7.1, tests 1 to 3: (secret key, public key, message, signature)
+        let vectors = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+            ),
+            (
+                "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+                "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+                "af82",
+                "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a"
+            ),
+        ];
+
+        for (secret_key, public_key, message, signature) in vectors {
+            let message = hex::decode(message).unwrap();
+            let signing_key = Ed25519SigningKey::from_bytes(from_hex(secret_key));
+            let verifying_key = signing_key.verifying_key();
+            assert_eq!(verifying_key.to_bytes(), from_hex::<32>(public_key));
+
+            let expected = Ed25519Signature::from_bytes(from_hex(signature));
+            assert_eq!(signing_key.sign(&message), expected);
+            assert_eq!(verifying_key.verify(&message, &expected), Ok(()));
+
+            let parsed = Ed25519VerifyingKey::from_bytes(from_hex(public_key)).unwrap();
+            assert_eq!(parsed.verify(&message, &expected), Ok(()));
+            assert_eq!(parsed.verify(b"other message", &expected), Err(Ed25519Error::Verification));
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = rand::rng();
+        let signing_key = Ed25519SigningKey::generate(&mut rng);
+        let verifying_key = signing_key.verifying_key();
+
+        let signature = signing_key.sign(b"Ed25519 test message");
+        assert_eq!(verifying_key.verify(b"Ed25519 test message", &signature), Ok(()));
+
+        //deterministic nonces: the same message always gives the same signature
+        assert_eq!(signing_key.sign(b"Ed25519 test message"), signature);
+
+        let mut tampered = signature.to_bytes();
+        tampered[40] ^= 1;
+        assert!(verifying_key.verify(b"Ed25519 test message", &Ed25519Signature::from_bytes(tampered)).is_err());
+
+        let other = Ed25519SigningKey::generate(&mut rng);
+        assert_eq!(other.verifying_key().verify(b"Ed25519 test message", &signature), Err(Ed25519Error::Verification));
+    }
+
+    #[test]
+    fn test_non_canonical_scalar_rejected() {
+        let signing_key = Ed25519SigningKey::from_bytes([7u8; 32]);
+        let signature = signing_key.sign(b"message").to_bytes();
+
+        //S + L verifies under the group law, but must be rejected as non-canonical
+        let s = U256::from_le_bytes(signature[32..].try_into().unwrap());
+        let mut malleated = signature;
+        malleated[32..].copy_from_slice(&s.wrapping_add(&L).to_le_bytes());
+
+        assert_eq!(
+            signing_key.verifying_key().verify(b"message", &Ed25519Signature::from_bytes(malleated)),
+            Err(Ed25519Error::NonCanonicalScalar)
+        );
+    }
+
+    #[test]
+    fn test_small_order_points_rejected() {
+        //identity (0, 1), (0, -1) of order 2 and (sqrt(-1), 0) of order 4
+        let small_order = [
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+        ];
+
+        let signing_key = Ed25519SigningKey::from_bytes([7u8; 32]);
+        let signature = signing_key.sign(b"message").to_bytes();
+
+        for encoding in small_order {
+            let bytes: [u8; 32] = from_hex(encoding);
+            assert_eq!(Ed25519VerifyingKey::from_bytes(bytes), Err(Ed25519Error::SmallOrderPoint));
+
+            //as R in a signature
+            let mut forged = signature;
+            forged[..32].copy_from_slice(&bytes);
+            assert_eq!(
+                signing_key.verifying_key().verify(b"message", &Ed25519Signature::from_bytes(forged)),
+                Err(Ed25519Error::SmallOrderPoint)
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_encodings_rejected() {
+        //y = p + 1 is a non-canonical encoding of y = 1
+        let non_canonical = from_hex("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+        assert_eq!(Ed25519VerifyingKey::from_bytes(non_canonical), Err(Ed25519Error::InvalidPoint));
+
+        //y = 2 has no x on the curve
+        let mut off_curve = [0u8; 32];
+        off_curve[0] = 2;
+        assert_eq!(Ed25519VerifyingKey::from_bytes(off_curve), Err(Ed25519Error::InvalidPoint));
+    }
+
+    #[test]
+    fn test_basepoint_order() {
+        let b = EdwardsPoint::basepoint();
+        assert_eq!(b.encode(), BASEPOINT_ENCODED);
+        assert!(b.scalar_multiplication(&L.to_le_bytes()).is_identity());
+        assert!(!b.scalar_multiplication(&L.wrapping_sub(&U256::ONE).to_le_bytes()).is_identity());
+    }
+}
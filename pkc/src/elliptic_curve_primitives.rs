@@ -1,5 +1,157 @@
+use crypto_bigint::{Odd, Uint, U256, U384, U576};
+use crypto_bigint::modular::{MontyForm, MontyParams};
 use num_bigint::BigInt;
+use crate::number_theory_primitives;
+use std::fmt;
 use std::ops::Rem;
+use std::sync::OnceLock;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+
+///point on an elliptic curve: either the point at infinity O, which is the
+///identity of the group, or an affine point (x, y) with 0 <= x, y < p
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Point {
+    Identity,
+    Affine(BigInt, BigInt)
+}
+
+impl Point {
+    pub fn is_identity(&self) -> bool {
+        matches!(self, Point::Identity)
+    }
+
+    ///affine x coordinate, None for the point at infinity
+    pub fn x(&self) -> Option<&BigInt> {
+        match self {
+            Point::Identity => None,
+            Point::Affine(x, _) => Some(x)
+        }
+    }
+
+    ///affine y coordinate, None for the point at infinity
+    pub fn y(&self) -> Option<&BigInt> {
+        match self {
+            Point::Identity => None,
+            Point::Affine(_, y) => Some(y)
+        }
+    }
+
+    ///to_sec1_bytes() encodes the point as in SEC 1 section 2.3.3, with coordinates
+    ///as big-endian strings of the field length l = ceil(log2(p) / 8) bytes
+    ///
+    /// output:
+    ///     - O = 0x00
+    ///     - compressed = 0x02 or 0x03 (y even or odd) || x
+    ///     - uncompressed = 0x04 || x || y
+    pub fn to_sec1_bytes(&self, curve: &EC, compressed: bool) -> Vec<u8> {
+        let (x, y) = match self {
+            Point::Identity => return vec![0x00],
+            Point::Affine(x, y) => (x, y)
+        };
+        let len = curve.field_length();
+
+        let mut bytes = Vec::with_capacity(1 + 2*len);
+        if compressed {
+            bytes.push(if y.bit(0) { 0x03 } else { 0x02 });
+            bytes.extend_from_slice(&to_fixed_bytes(x, len));
+        } else {
+            bytes.push(0x04);
+            bytes.extend_from_slice(&to_fixed_bytes(x, len));
+            bytes.extend_from_slice(&to_fixed_bytes(y, len));
+        }
+        bytes
+    }
+
+    ///from_sec1_bytes() decodes a compressed or uncompressed point (SEC 1 section
+    ///2.3.4), recovering y for compressed points from y^2 = x^3 + ax + b. coordinates
+    ///must be below p and the point must lie on E. hybrid encodings (0x06, 0x07) are
+    ///not accepted
+    pub fn from_sec1_bytes(curve: &EC, bytes: &[u8]) -> Result<Point, EncodingError> {
+        let len = curve.field_length();
+        let p = curve.p();
+
+        let (tag, coordinates) = match bytes.split_first() {
+            Some((tag, rest)) => (*tag, rest),
+            None => return Err(EncodingError::InvalidLength)
+        };
+        let coordinate = |i: usize| {
+            let c = BigInt::from_bytes_be(num_bigint::Sign::Plus, &coordinates[i*len..(i + 1)*len]);
+            if &c >= p { Err(EncodingError::CoordinateOutOfRange) } else { Ok(c) }
+        };
+
+        match (tag, coordinates.len()) {
+            (0x00, 0) => Ok(Point::Identity),
+            (0x02 | 0x03, l) if l == len => {
+                let x = coordinate(0)?;
+                let alpha = modulo(&(x.pow(3) + curve.a()*&x + curve.b()), p);
+                let beta = number_theory_primitives::sqrt_mod_prime(&alpha, p).ok_or(EncodingError::PointNotOnCurve)?;
+
+                //pick the root whose parity matches the tag, 0 has no odd counterpart
+                let y = if beta.bit(0) == (tag == 0x03) { beta } else { modulo(&-beta, p) };
+                if y.bit(0) != (tag == 0x03) {
+                    return Err(EncodingError::PointNotOnCurve)
+                }
+                Ok(Point::Affine(x, y))
+            },
+            (0x04, l) if l == 2*len => {
+                let point = Point::Affine(coordinate(0)?, coordinate(1)?);
+                if !curve.is_on_curve(&point) {
+                    return Err(EncodingError::PointNotOnCurve)
+                }
+                Ok(point)
+            },
+            (0x00 | 0x02 | 0x03 | 0x04, _) => Err(EncodingError::InvalidLength),
+            _ => Err(EncodingError::InvalidTag)
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    InvalidLength,
+    InvalidTag,
+    CoordinateOutOfRange,
+    PointNotOnCurve,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::InvalidLength => write!(f, "encoded point has the wrong length for the curve"),
+            EncodingError::InvalidTag => write!(f, "leading byte must be 0x00, 0x02, 0x03 or 0x04"),
+            EncodingError::CoordinateOutOfRange => write!(f, "coordinates must satisfy 0 <= x, y < p"),
+            EncodingError::PointNotOnCurve => write!(f, "encoded point does not lie on the curve"),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+
+///error from the constant-time routines, which run on fixed-width integers of at most
+///MAX_FIXED_WIDTH_BITS (enough for P-521)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixedWidthError {
+    PrimeTooWide
+}
+
+impl fmt::Display for FixedWidthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedWidthError::PrimeTooWide => write!(f, "constant-time arithmetic supports primes of at most {} bits", MAX_FIXED_WIDTH_BITS),
+        }
+    }
+}
+
+impl std::error::Error for FixedWidthError {}
+
+//widest prime the constant-time routines handle
+pub const MAX_FIXED_WIDTH_BITS: u64 = U576::BITS as u64;
+
+//miller-rabin rounds for the group order n
+const PRIMALITY_ROUNDS: usize = 40;
 
 
 ///elliptic curve construction:
@@ -10,8 +162,9 @@ use std::ops::Rem;
 /// 1. construction
 /// 2. point addition
 /// 3. point doubling
-/// 4. double and add algorithm 
-#[derive(Debug)]
+/// 4. constant-time Montgomery ladder for secret scalars
+/// 5. double and add algorithm for public scalars
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EC {
     a: BigInt,
     b: BigInt,
@@ -21,14 +174,9 @@ pub struct EC {
 
 impl EC {
     //p = large prime
-    pub fn new(mut a: BigInt, mut b: BigInt, p: BigInt) -> EC {
-        if a > p {
-            a = a.rem(&p)
-        }
-
-        if b > p {
-            b = b.rem(&p)
-        }
+    pub fn new(a: BigInt, b: BigInt, p: BigInt) -> EC {
+        let a = modulo(&a, &p);
+        let b = modulo(&b, &p);
 
         //assert non-singularity
         let expr: BigInt = BigInt::from(4)*a.pow(3) + BigInt::from(27)*b.pow(2);
@@ -39,110 +187,1030 @@ impl EC {
         Self { a, b, p }
     }
 
-    //add two unique points
-    pub fn point_addition_unique(&self, P: &(BigInt, BigInt), Q: &(BigInt, BigInt)) -> (BigInt, BigInt) {
-        
-        let a = &self.a;
-        let b = &self.b;
-        let p = &self.p;
+    pub fn a(&self) -> &BigInt {
+        &self.a
+    }
 
-        let P_x = &P.0;
-        let P_y = &P.1;
-        let lhs = (&P_y.pow(2)).rem(p);
-        let rhs = (&P_x.pow(3)+ a*P_x + b).rem(p);
-        assert_eq!(lhs, rhs, "point {:?} does not lie on E", P);
+    pub fn b(&self) -> &BigInt {
+        &self.b
+    }
 
-        let Q_x = &Q.0;
-        let Q_y = &Q.1;
-        let lhs = (&Q_y.pow(2)).rem(p);
-        let rhs = (&Q_x.pow(3)+ a*Q_x + b).rem(p);
-        assert_eq!(lhs, rhs, "point {:?} does not lie on E", Q);
+    pub fn p(&self) -> &BigInt {
+        &self.p
+    }
 
-        let s = (P_y - Q_y)*((P_x - Q_x).modinv(p).unwrap());
+    ///length in bytes of a field element, ceil(log2(p) / 8)
+    pub fn field_length(&self) -> usize {
+        self.p.bits().div_ceil(8) as usize
+    }
 
-        let mut R_x = (s.pow(2) - P_x - Q_x).rem(p);
-        let mut R_y = (s*(P_x - &R_x) - P_y).rem(p);
+    //check P satisfies y^2 = x^3 + ax + b (mod p), O is always on the curve
+    pub fn is_on_curve(&self, point: &Point) -> bool {
+        match point {
+            Point::Identity => true,
+            Point::Affine(x, y) => {
+                let lhs = modulo(&y.pow(2), &self.p);
+                let rhs = modulo(&(x.pow(3) + &self.a*x + &self.b), &self.p);
 
-        if R_x < BigInt::ZERO {
-            while R_x < BigInt::ZERO {
-                R_x = R_x.checked_add(p).unwrap();
+                lhs == rhs
             }
         }
+    }
+
+    //-P = (x, -y), -O = O
+    pub fn negate(&self, point: &Point) -> Point {
+        match point {
+            Point::Identity => Point::Identity,
+            Point::Affine(x, y) => Point::Affine(x.clone(), modulo(&-y, &self.p))
+        }
+    }
+
+    //group law P + Q, covering O + Q = Q, P + O = P, P + (-P) = O and P + P = 2P
+    pub fn point_addition(&self, point_p: &Point, point_q: &Point) -> Point {
+        assert!(self.is_on_curve(point_p), "point {:?} does not lie on E", point_p);
+        assert!(self.is_on_curve(point_q), "point {:?} does not lie on E", point_q);
+
+        let p = &self.p;
 
-        if R_y < BigInt::ZERO {
-            while R_y < BigInt::ZERO {
-                R_y = R_y.checked_add(p).unwrap();
+        let ((p_x, p_y), (q_x, q_y)) = match (point_p, point_q) {
+            (Point::Identity, _) => return point_q.clone(),
+            (_, Point::Identity) => return point_p.clone(),
+            (Point::Affine(p_x, p_y), Point::Affine(q_x, q_y)) => ((p_x, p_y), (q_x, q_y))
+        };
+
+        if p_x == q_x {
+            //same x means Q = P or Q = -P
+            if p_y == q_y {
+                return self.point_double(point_p)
             }
+            return Point::Identity
         }
 
-        (R_x, R_y)
+        let s = modulo(&((p_y - q_y)*((p_x - q_x).modinv(p).unwrap())), p);
+
+        let r_x = modulo(&(s.pow(2) - p_x - q_x), p);
+        let r_y = modulo(&(s*(p_x - &r_x) - p_y), p);
+
+        Point::Affine(r_x, r_y)
     }
 
-    //point doubling
-    pub fn point_double(&self, P: &(BigInt, BigInt)) -> (BigInt, BigInt) {
+    //point doubling, where 2P = O when P has y = 0
+    pub fn point_double(&self, point: &Point) -> Point {
+        assert!(self.is_on_curve(point), "point {:?} does not lie on E", point);
+
         let a = &self.a;
-        let b = &self.b;
         let p = &self.p;
 
-        let P_x = &P.0;
-        let P_y = &P.1;
-        let lhs = (&P_y.pow(2)).rem(p);
-        let rhs = (&P_x.pow(3)+ a*P_x + b).rem(p);
-        assert_eq!(lhs, rhs, "point {:?} does not lie on E", P);
+        let (p_x, p_y) = match point {
+            Point::Identity => return Point::Identity,
+            Point::Affine(p_x, p_y) => (p_x, p_y)
+        };
+
+        if p_y == &BigInt::ZERO {
+            return Point::Identity
+        }
 
-        let s: BigInt = (3*P_x.pow(2) + a) * (BigInt::from(P_y*2)).modinv(p).unwrap();
+        let s: BigInt = modulo(&((3*p_x.pow(2) + a) * (p_y*BigInt::from(2)).modinv(p).unwrap()), p);
 
-        let mut R_x = (s.pow(2) - BigInt::from(2)*P_x).rem(p);
-        let mut R_y = (s*(P_x - &R_x) - P_y).rem(p);
+        let r_x = modulo(&(s.pow(2) - BigInt::from(2)*p_x), p);
+        let r_y = modulo(&(s*(p_x - &r_x) - p_y), p);
 
-        if R_x < BigInt::ZERO {
-            while R_x < BigInt::ZERO {
-                R_x = R_x.checked_add(p).unwrap();
-            }
+        Point::Affine(r_x, r_y)
+    }
+
+    //Montgomery ladder n*P for any integer n: 0*P = O and (-n)*P = n*(-P)
+    //
+    //this is the scalar multiplication for secret scalars (private keys, nonces).
+    //the ladder runs over a fixed number of bits, bitlen(p) + 1, which covers any
+    //scalar reduced mod the group order. every step is one complete addition and one
+    //doubling on fixed-width crypto-bigint limbs, with the two ladder points
+    //swapped by constant-time selection instead of branching on the bit. only the
+    //sign of n and scalars wider than the fixed width change the running time
+    //
+    //returns FixedWidthError::PrimeTooWide for primes wider than MAX_FIXED_WIDTH_BITS
+    pub fn point_n_addition(&self, point: Point, n: BigInt) -> Result<Point, FixedWidthError> {
+        assert!(self.is_on_curve(&point), "point {:?} does not lie on E", point);
+
+        let (point, n) = if n < BigInt::ZERO {
+            (self.negate(&point), -n)
+        } else {
+            (point, n)
+        };
+
+        //pick the limb count from the size of p, so P-256 runs on 256-bit integers
+        let p_bits = self.p.bits();
+        if p_bits <= U256::BITS as u64 {
+            Ok(self.montgomery_ladder::<{ U256::LIMBS }>(&point, &n))
+        } else if p_bits <= U384::BITS as u64 {
+            Ok(self.montgomery_ladder::<{ U384::LIMBS }>(&point, &n))
+        } else if p_bits <= U576::BITS as u64 {
+            Ok(self.montgomery_ladder::<{ U576::LIMBS }>(&point, &n))
+        } else {
+            Err(FixedWidthError::PrimeTooWide)
         }
+    }
 
-        if R_y < BigInt::ZERO {
-            while R_y < BigInt::ZERO {
-                R_y = R_y.checked_add(p).unwrap();
-            }
+    fn montgomery_ladder<const LIMBS: usize>(&self, point: &Point, n: &BigInt) -> Point {
+        let field = PrimeField::<LIMBS>::new(&self.p);
+        let a = field.element(&self.a);
+        let b_3 = field.element(&(BigInt::from(3) * &self.b).rem(&self.p));
+
+        //fixed-width big-endian copy of the scalar
+        let width = (self.p.bits() + 1).max(n.bits());
+        let scalar = to_fixed_bytes(n, width.div_ceil(8) as usize);
+
+        //invariant: R_1 - R_0 = P
+        let mut r_0 = field.identity();
+        let mut r_1 = field.projective(point);
+        let mut swapped = Choice::from(0);
+
+        for i in (0..width).rev() {
+            //swap only when the bit differs from the previous one, so R_0 picks up the
+            //doubling exactly when the bit is 0
+            let byte = scalar[scalar.len() - 1 - (i / 8) as usize];
+            let bit = Choice::from((byte >> (i % 8)) & 1);
+            ProjectivePoint::conditional_swap(&mut r_0, &mut r_1, bit ^ swapped);
+            swapped = bit;
+
+            r_1 = complete_addition(&r_0, &r_1, &a, &b_3);
+            r_0 = complete_addition(&r_0, &r_0, &a, &b_3);
         }
+        ProjectivePoint::conditional_swap(&mut r_0, &mut r_1, swapped);
 
-        (R_x, R_y)
+        //the complete formulas fail when the two summands differ by a point of order 2,
+        //and R_1 - R_0 = P, so a point of order 2 (y = 0, only on curves of even order)
+        //leaves garbage in R_0. its multiples are O for even n and P for odd n, which
+        //is selected in without branching on y or n
+        let order_two = match point {
+            Point::Identity => Choice::from(0),
+            Point::Affine(_, y) => to_uint::<LIMBS>(y).ct_eq(&Uint::ZERO)
+        };
+        let odd = Choice::from(scalar[scalar.len() - 1] & 1);
+        let torsion_multiple = ProjectivePoint::conditional_select(&field.identity(), &field.projective(point), odd);
+        r_0 = ProjectivePoint::conditional_select(&r_0, &torsion_multiple, order_two);
+
+        field.to_affine(&r_0)
     }
 
-    //double and add algorithm
-    pub fn point_n_addition(&self, P: (BigInt, BigInt), n: BigInt) -> (BigInt, BigInt) {
+    //double and add algorithm, for any integer n: 0*P = O and (-n)*P = n*(-P)
+    //
+    //the running point R is kept in Jacobian coordinates so that no step needs an
+    //inversion, and only the final result is converted back to affine
+    //
+    //running time depends on the bits of n, so only use it when n is public, e.g. for
+    //u_1*G + u_2*Q in ECDSA verification
+    pub fn point_n_addition_vartime(&self, point: Point, n: BigInt) -> Point {
+        assert!(self.is_on_curve(&point), "point {:?} does not lie on E", point);
 
-        let a = &self.a;
-        let b = &self.b;
-        let p = &self.p;
+        let (point, n) = if n < BigInt::ZERO {
+            (self.negate(&point), -n)
+        } else {
+            (point, n)
+        };
 
-        let P_x = &P.0;
-        let P_y = &P.1;
-        let lhs = (&P_y.pow(2)).rem(p);
-        let rhs = (&P_x.pow(3)+ a*P_x + b).rem(p);
-        assert_eq!(lhs, rhs, "point {:?} does not lie on E", P);
-        
+        let (x, y) = match &point {
+            Point::Identity => return Point::Identity,
+            Point::Affine(x, y) => (x, y)
+        };
 
         // -----------------------------------------
         let n_bin = format!("{:b}", n);
 
-        //initialise R as point at infinity (placeholder values)
-        let mut R:(BigInt, BigInt) = (BigInt::ZERO, BigInt::ZERO);
+        //R starts at O, so leading zero bits (and n = 0) leave it unchanged
+        let mut result = JacobianPoint::identity();
 
         for digit in n_bin.chars() {
-            //adding point at infinity to orginial point P at first iteration
-            if R == (BigInt::ZERO, BigInt::ZERO) {
-                R = P.clone();
-            }
+            result = self.jacobian_double(&result);
             if digit == '1' {
-                let twoR = self.point_double(&R);
-                R = self.point_addition_unique(&twoR, &P);
-            } else {
-                R = self.point_double(&R)
+                result = self.jacobian_add_affine(&result, x, y);
             }
         }
         
-        R
+        self.to_affine(&result)
+    }
+
+    //(X : Y : Z) -> (X/Z^2, Y/Z^3), the single inversion of a scalar multiplication
+    fn to_affine(&self, point: &JacobianPoint) -> Point {
+        let p = &self.p;
+
+        if point.is_identity() {
+            return Point::Identity
+        }
+
+        let z_inv = point.z.modinv(p).unwrap();
+        let z_inv_squared = (&z_inv * &z_inv).rem(p);
+        let x = modulo(&(&point.x * &z_inv_squared), p);
+        let y = modulo(&(&point.y * z_inv_squared * z_inv), p);
+
+        Point::Affine(x, y)
+    }
+
+    //Jacobian doubling (dbl-2007-bl with 2YZ for Z3), valid for any a:
+    // S = 4XY^2, M = 3X^2 + aZ^4
+    // X3 = M^2 - 2S, Y3 = M(S - X3) - 8Y^4, Z3 = 2YZ
+    fn jacobian_double(&self, point: &JacobianPoint) -> JacobianPoint {
+        let p = &self.p;
+
+        //2P = O for P = O or P of order two
+        if point.is_identity() || point.y == BigInt::ZERO {
+            return JacobianPoint::identity()
+        }
+
+        let (x, y, z) = (&point.x, &point.y, &point.z);
+
+        let y_squared = (y * y).rem(p);
+        let z_squared = (z * z).rem(p);
+        let s = (BigInt::from(4) * x * &y_squared).rem(p);
+        let m = (BigInt::from(3) * x * x + &self.a * &z_squared * &z_squared).rem(p);
+
+        let x_3 = modulo(&(&m * &m - BigInt::from(2) * &s), p);
+        let y_3 = modulo(&(m * (s - &x_3) - BigInt::from(8) * &y_squared * &y_squared), p);
+        let z_3 = (BigInt::from(2) * y * z).rem(p);
+
+        JacobianPoint { x: x_3, y: y_3, z: z_3 }
+    }
+
+    //mixed Jacobian + affine addition (madd-2007-bl without the Z2 = 1 terms):
+    // U2 = x2 Z1^2, S2 = y2 Z1^3, H = U2 - X1, r = S2 - Y1
+    // X3 = r^2 - H^3 - 2 X1 H^2, Y3 = r(X1 H^2 - X3) - Y1 H^3, Z3 = Z1 H
+    //H = 0 means the x coordinates agree, so the operands are equal or inverses
+    fn jacobian_add_affine(&self, point: &JacobianPoint, x_2: &BigInt, y_2: &BigInt) -> JacobianPoint {
+        let p = &self.p;
+
+        if point.is_identity() {
+            return JacobianPoint { x: x_2.clone(), y: y_2.clone(), z: BigInt::from(1) }
+        }
+
+        let (x_1, y_1, z_1) = (&point.x, &point.y, &point.z);
+
+        let z_1_squared = (z_1 * z_1).rem(p);
+        let u_2 = (x_2 * &z_1_squared).rem(p);
+        let s_2 = (y_2 * z_1 * &z_1_squared).rem(p);
+        let h = modulo(&(u_2 - x_1), p);
+        let r = modulo(&(s_2 - y_1), p);
+
+        if h == BigInt::ZERO {
+            if r == BigInt::ZERO {
+                return self.jacobian_double(point)
+            }
+            return JacobianPoint::identity()
+        }
+
+        let h_squared = (&h * &h).rem(p);
+        let h_cubed = (&h_squared * &h).rem(p);
+        let x_1_h_squared = (x_1 * h_squared).rem(p);
+
+        let x_3 = modulo(&(&r * &r - &h_cubed - BigInt::from(2) * &x_1_h_squared), p);
+        let y_3 = modulo(&(r * (x_1_h_squared - &x_3) - y_1 * h_cubed), p);
+        let z_3 = (z_1 * h).rem(p);
+
+        JacobianPoint { x: x_3, y: y_3, z: z_3 }
+    }
+}
+
+//arithmetic mod the curve prime on fixed-width Montgomery form limbs
+struct PrimeField<const LIMBS: usize> {
+    params: MontyParams<LIMBS>,
+    p_minus_two: Uint<LIMBS>
+}
+
+impl<const LIMBS: usize> PrimeField<LIMBS> {
+    fn new(p: &BigInt) -> PrimeField<LIMBS> {
+        let modulus = Odd::new(to_uint::<LIMBS>(p)).expect("curve prime must be odd");
+
+        //the modulus is public, so the variable-time setup is fine
+        PrimeField {
+            params: MontyParams::new_vartime(modulus),
+            p_minus_two: to_uint(&(p - BigInt::from(2)))
+        }
+    }
+
+    //x must already be reduced mod p
+    fn element(&self, x: &BigInt) -> MontyForm<LIMBS> {
+        MontyForm::new(&to_uint(x), self.params)
+    }
+
+    fn to_bigint(&self, x: &MontyForm<LIMBS>) -> BigInt {
+        x.retrieve().as_words().iter().rev()
+            .fold(BigInt::ZERO, |acc, word| (acc << (8 * size_of_val(word))) + BigInt::from(*word))
+    }
+
+    //O = (0 : 1 : 0)
+    fn identity(&self) -> ProjectivePoint<LIMBS> {
+        ProjectivePoint {
+            x: MontyForm::zero(self.params),
+            y: MontyForm::one(self.params),
+            z: MontyForm::zero(self.params)
+        }
+    }
+
+    fn projective(&self, point: &Point) -> ProjectivePoint<LIMBS> {
+        match point {
+            Point::Identity => self.identity(),
+            Point::Affine(x, y) => ProjectivePoint {
+                x: self.element(x),
+                y: self.element(y),
+                z: MontyForm::one(self.params)
+            }
+        }
+    }
+
+    //x^-1 = x^(p-2) by Fermat, which unlike a gcd-based inverse runs in fixed time.
+    //0 maps to 0
+    fn invert(&self, x: &MontyForm<LIMBS>) -> MontyForm<LIMBS> {
+        x.pow(&self.p_minus_two)
+    }
+
+    //(X : Y : Z) -> (X/Z, Y/Z), with Z = 0 for O
+    fn to_affine(&self, point: &ProjectivePoint<LIMBS>) -> Point {
+        let z_inv = self.invert(&point.z);
+        let x = self.to_bigint(&(point.x * z_inv));
+        let y = self.to_bigint(&(point.y * z_inv));
+
+        //only the public result is branched on
+        if self.to_bigint(&point.z) == BigInt::ZERO {
+            return Point::Identity
+        }
+
+        Point::Affine(x, y)
+    }
+}
+
+//x^-1 mod an odd prime p, for secret x such as an ECDSA nonce. x must already be
+//reduced mod p. unlike number_theory_primitives::multiplicative_inverse, the running
+//time depends only on the size of p. primes wider than MAX_FIXED_WIDTH_BITS give
+//FixedWidthError::PrimeTooWide
+pub fn invert_mod_prime(x: &BigInt, p: &BigInt) -> Result<BigInt, FixedWidthError> {
+    fn invert<const LIMBS: usize>(x: &BigInt, p: &BigInt) -> BigInt {
+        let field = PrimeField::<LIMBS>::new(p);
+        field.to_bigint(&field.invert(&field.element(x)))
+    }
+
+    let p_bits = p.bits();
+    if p_bits <= U256::BITS as u64 {
+        Ok(invert::<{ U256::LIMBS }>(x, p))
+    } else if p_bits <= U384::BITS as u64 {
+        Ok(invert::<{ U384::LIMBS }>(x, p))
+    } else if p_bits <= U576::BITS as u64 {
+        Ok(invert::<{ U576::LIMBS }>(x, p))
+    } else {
+        Err(FixedWidthError::PrimeTooWide)
+    }
+}
+
+//point (X : Y : Z) in homogeneous projective coordinates, standing for the affine
+//point (X/Z, Y/Z), with Z = 0 for the point at infinity
+#[derive(Clone, Copy)]
+struct ProjectivePoint<const LIMBS: usize> {
+    x: MontyForm<LIMBS>,
+    y: MontyForm<LIMBS>,
+    z: MontyForm<LIMBS>
+}
+
+impl<const LIMBS: usize> ConditionallySelectable for ProjectivePoint<LIMBS> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        ProjectivePoint {
+            x: MontyForm::conditional_select(&a.x, &b.x, choice),
+            y: MontyForm::conditional_select(&a.y, &b.y, choice),
+            z: MontyForm::conditional_select(&a.z, &b.z, choice)
+        }
+    }
+}
+
+//non-negative x < 2^(8 * len) as len big-endian bytes
+fn to_fixed_bytes(x: &BigInt, len: usize) -> Vec<u8> {
+    let (_, bytes) = x.to_bytes_be();
+    let mut fixed = vec![0u8; len - bytes.len()];
+    fixed.extend_from_slice(&bytes);
+    fixed
+}
+
+fn to_uint<const LIMBS: usize>(x: &BigInt) -> Uint<LIMBS> {
+    Uint::from_be_slice(&to_fixed_bytes(x, Uint::<LIMBS>::BYTES))
+}
+
+//complete addition for short Weierstrass curves with any a (Renes, Costello and
+//Batina 2016, algorithm 1). there are no special cases: it is correct for P + Q,
+//P + P, P + (-P) and identity operands on curves of odd order, so the ladder never
+//branches on the points. b_3 = 3b
+fn complete_addition<const LIMBS: usize>(p_1: &ProjectivePoint<LIMBS>, p_2: &ProjectivePoint<LIMBS>, a: &MontyForm<LIMBS>, b_3: &MontyForm<LIMBS>) -> ProjectivePoint<LIMBS> {
+    let ProjectivePoint { x: x_1, y: y_1, z: z_1 } = *p_1;
+    let ProjectivePoint { x: x_2, y: y_2, z: z_2 } = *p_2;
+    let (a, b_3) = (*a, *b_3);
+
+    let mut t_0 = x_1 * x_2;
+    let mut t_1 = y_1 * y_2;
+    let mut t_2 = z_1 * z_2;
+    let mut t_3 = x_1 + y_1;
+    let mut t_4 = x_2 + y_2;
+    t_3 *= t_4;
+    t_4 = t_0 + t_1;
+    t_3 -= t_4;
+    t_4 = x_1 + z_1;
+    let mut t_5 = x_2 + z_2;
+    t_4 *= t_5;
+    t_5 = t_0 + t_2;
+    t_4 -= t_5;
+    t_5 = y_1 + z_1;
+    let mut x_3 = y_2 + z_2;
+    t_5 *= x_3;
+    x_3 = t_1 + t_2;
+    t_5 -= x_3;
+    let mut z_3 = a * t_4;
+    x_3 = b_3 * t_2;
+    z_3 += x_3;
+    x_3 = t_1 - z_3;
+    z_3 += t_1;
+    let mut y_3 = x_3 * z_3;
+    t_1 = t_0 + t_0;
+    t_1 += t_0;
+    t_2 *= a;
+    t_4 *= b_3;
+    t_1 += t_2;
+    t_2 = t_0 - t_2;
+    t_2 *= a;
+    t_4 += t_2;
+    t_0 = t_1 * t_4;
+    y_3 += t_0;
+    t_0 = t_5 * t_4;
+    x_3 *= t_3;
+    x_3 -= t_0;
+    t_0 = t_3 * t_1;
+    z_3 *= t_5;
+    z_3 += t_0;
+
+    ProjectivePoint { x: x_3, y: y_3, z: z_3 }
+}
+
+//point (X : Y : Z) in Jacobian coordinates, standing for the affine point
+//(X/Z^2, Y/Z^3), with Z = 0 for the point at infinity
+struct JacobianPoint {
+    x: BigInt,
+    y: BigInt,
+    z: BigInt
+}
+
+impl JacobianPoint {
+    fn identity() -> JacobianPoint {
+        JacobianPoint { x: BigInt::from(1), y: BigInt::from(1), z: BigInt::ZERO }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.z == BigInt::ZERO
+    }
+}
+
+//least non-negative residue of x mod m
+fn modulo(x: &BigInt, m: &BigInt) -> BigInt {
+    let r = x.rem(m);
+    if r < BigInt::ZERO {
+        return r + m
+    }
+    r
+}
+
+
+///elliptic curve domain parameters (p, a, b, G, n, h):
+///     - curve = E: y^2 = x^3 + ax + b (mod p)
+///     - generator = base point G on E
+///     - order = prime order n of G
+///     - cofactor = h = #E(F_p) / n
+///
+///protocols such as ECDSA do their scalar arithmetic mod n, not mod p
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainParameters {
+    curve: EC,
+    generator: Point,
+    order: BigInt,
+    cofactor: BigInt
+}
+
+impl DomainParameters {
+    //new() checks G is an affine point on E, that n is prime with n*G = O, and that
+    //p and n fit the constant-time scalar arithmetic, and panics otherwise
+    pub fn new(curve: EC, generator: Point, order: BigInt, cofactor: BigInt) -> DomainParameters {
+        assert!(curve.p.bits() <= MAX_FIXED_WIDTH_BITS, "p must be at most {} bits", MAX_FIXED_WIDTH_BITS);
+        assert!(order.bits() <= MAX_FIXED_WIDTH_BITS, "n must be at most {} bits", MAX_FIXED_WIDTH_BITS);
+        assert!(!generator.is_identity(), "generator must not be the point at infinity");
+        assert!(curve.is_on_curve(&generator), "generator {:?} does not lie on E", generator);
+        assert!(order > BigInt::from(1), "generator order must be greater than 1");
+        assert!(number_theory_primitives::is_prime(&order, PRIMALITY_ROUNDS), "generator order n must be prime");
+        assert!(cofactor >= BigInt::from(1), "cofactor must be positive");
+
+        let n_g = curve.point_n_addition_vartime(generator.clone(), order.clone());
+        assert!(n_g.is_identity(), "n*G must be the point at infinity");
+
+        Self { curve, generator, order, cofactor }
+    }
+
+    pub fn curve(&self) -> &EC {
+        &self.curve
+    }
+
+    pub fn generator(&self) -> &Point {
+        &self.generator
+    }
+
+    pub fn order(&self) -> &BigInt {
+        &self.order
+    }
+
+    pub fn cofactor(&self) -> &BigInt {
+        &self.cofactor
+    }
+}
+
+impl From<NamedCurve> for DomainParameters {
+    fn from(curve: NamedCurve) -> DomainParameters {
+        curve.domain_parameters()
+    }
+}
+
+
+///standard curves with built-in domain parameters (SEC 2 v2, FIPS 186-4 D.1.2)
+///
+///NIST P-256, P-384 and P-521 (secp256r1, secp384r1, secp521r1) and the Koblitz
+///curve secp256k1. all have prime order, so cofactor h = 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NamedCurve {
+    P256,
+    P384,
+    P521,
+    Secp256k1
+}
+
+impl NamedCurve {
+    pub const ALL: [NamedCurve; 4] = [NamedCurve::P256, NamedCurve::P384, NamedCurve::P521, NamedCurve::Secp256k1];
+
+    ///standard name, e.g. "P-256"
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamedCurve::P256 => "P-256",
+            NamedCurve::P384 => "P-384",
+            NamedCurve::P521 => "P-521",
+            NamedCurve::Secp256k1 => "secp256k1"
+        }
+    }
+
+    ///from_name() looks a curve up by its NIST or SEC 2 name, ignoring case
+    pub fn from_name(name: &str) -> Option<NamedCurve> {
+        match name.to_ascii_lowercase().as_str() {
+            "p-256" | "p256" | "secp256r1" | "prime256v1" => Some(NamedCurve::P256),
+            "p-384" | "p384" | "secp384r1" => Some(NamedCurve::P384),
+            "p-521" | "p521" | "secp521r1" => Some(NamedCurve::P521),
+            "secp256k1" => Some(NamedCurve::Secp256k1),
+            _ => None
+        }
+    }
+
+    ///domain_parameters() returns (p, a, b, G, n, h). they are built and validated by
+    ///DomainParameters::new (G on the curve, n prime and n*G = O) on first use and
+    ///cloned from a cache afterwards
+    pub fn domain_parameters(&self) -> DomainParameters {
+        static CACHE: [OnceLock<DomainParameters>; 4] = [const { OnceLock::new() }; 4];
+
+        CACHE[*self as usize].get_or_init(|| self.build_domain_parameters()).clone()
+    }
+
+    fn build_domain_parameters(&self) -> DomainParameters {
+        //(p, a, b, G_x, G_y, n)
+        let (p, a, b, g_x, g_y, n) = match self {
+            NamedCurve::P256 => (
+                "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+                "ffffffff00000001000000000000000000000000fffffffffffffffffffffffc",
+                "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+                "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+                "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+                "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"
+            ),
+            NamedCurve::P384 => (
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000fffffffc",
+                "b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef",
+                "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7",
+                "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973"
+            ),
+            NamedCurve::P521 => (
+                "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "01fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffc",
+                "0051953eb9618e1c9a1f929a21a0b68540eea2da725b99b315f3b8b489918ef109e156193951ec7e937b1652c0bd3bb1bf073573df883d2c34f1ef451fd46b503f00",
+                "00c6858e06b70404e9cd9e3ecb662395b4429c648139053fb521f828af606b4d3dbaa14b5e77efe75928fe1dc127a2ffa8de3348b3c1856a429bf97e7e31c2e5bd66",
+                "011839296a789a3bc0045c8a5fb42c7d1bd998f54449579b446817afbd17273e662c97ee72995ef42640c550b9013fad0761353c7086a272c24088be94769fd16650",
+                "01fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffa51868783bf2f966b7fcc0148f709a5d03bb5c9b8899c47aebb6fb71e91386409"
+            ),
+            NamedCurve::Secp256k1 => (
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "00",
+                "07",
+                "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+            )
+        };
+
+        let from_hex = |hex: &str| BigInt::parse_bytes(hex.as_bytes(), 16).unwrap();
+        let curve = EC::new(from_hex(a), from_hex(b), from_hex(p));
+        let generator = Point::Affine(from_hex(g_x), from_hex(g_y));
+
+        DomainParameters::new(curve, generator, from_hex(n), BigInt::from(1))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    //E: y^2 = x^3 + 2x + 2 mod 17, whose 19 points form a cyclic group generated by (5, 1)
+    fn small_curve() -> (EC, Point) {
+        let curve = EC::new(BigInt::from(2), BigInt::from(2), BigInt::from(17));
+        (curve, Point::Affine(BigInt::from(5), BigInt::from(1)))
+    }
+
+    fn affine(x: i64, y: i64) -> Point {
+        Point::Affine(BigInt::from(x), BigInt::from(y))
+    }
+
+    #[test]
+    fn test_addition_cases() {
+        let (curve, g) = small_curve();
+        let minus_g = curve.negate(&g);
+        assert_eq!(minus_g, affine(5, 16));
+
+        //identity operands
+        assert_eq!(curve.point_addition(&g, &Point::Identity), g);
+        assert_eq!(curve.point_addition(&Point::Identity, &g), g);
+        assert_eq!(curve.point_addition(&Point::Identity, &Point::Identity), Point::Identity);
+
+        //P + (-P) = O
+        assert_eq!(curve.point_addition(&g, &minus_g), Point::Identity);
+
+        //P + P falls through to doubling
+        assert_eq!(curve.point_addition(&g, &g), affine(6, 3));
+        assert_eq!(curve.point_double(&g), affine(6, 3));
+        assert_eq!(curve.point_addition(&affine(6, 3), &g), affine(10, 6));
+    }
+
+    #[test]
+    fn test_doubling_two_torsion() {
+        //E: y^2 = x^3 - x mod 23 has the points of order two (0, 0), (1, 0) and (22, 0)
+        let curve = EC::new(BigInt::from(-1), BigInt::ZERO, BigInt::from(23));
+
+        for x in [0, 1, 22] {
+            assert_eq!(curve.point_double(&affine(x, 0)), Point::Identity);
+        }
+        assert_eq!(curve.point_double(&Point::Identity), Point::Identity);
+    }
+
+    #[test]
+    fn test_ladder_even_order_curve() {
+        //E: y^2 = x^3 - x mod 23 has 24 points. the complete formulas break on T = (0, 0)
+        //of order 2, so the ladder has to give the same answers as double and add
+        let curve = EC::new(BigInt::from(-1), BigInt::ZERO, BigInt::from(23));
+        let t = affine(0, 0);
+
+        assert_eq!(curve.point_n_addition(t.clone(), BigInt::from(1)).unwrap(), t);
+        assert_eq!(curve.point_n_addition(t.clone(), BigInt::from(2)).unwrap(), Point::Identity);
+        assert_eq!(curve.point_n_addition(t.clone(), BigInt::from(3)).unwrap(), t);
+        assert_eq!(curve.point_n_addition(t.clone(), BigInt::from(-1)).unwrap(), t);
+
+        //every point and scalar up to the group exponent
+        for x in 0..23 {
+            for y in 0..23 {
+                let point = affine(x, y);
+                if !curve.is_on_curve(&point) {
+                    continue
+                }
+                for n in -1..=25 {
+                    assert_eq!(
+                        curve.point_n_addition(point.clone(), BigInt::from(n)).unwrap(),
+                        curve.point_n_addition_vartime(point.clone(), BigInt::from(n))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invert_mod_prime() {
+        for x in 0..19 {
+            let expected = BigInt::from(x).modinv(&BigInt::from(19)).unwrap_or(BigInt::ZERO);
+            assert_eq!(invert_mod_prime(&BigInt::from(x), &BigInt::from(19)).unwrap(), expected);
+        }
+
+        //the orders of the named curves, one per limb count
+        for curve in [NamedCurve::P256, NamedCurve::P384, NamedCurve::P521] {
+            let order = curve.domain_parameters().order().clone();
+            for _ in 0..10 {
+                let x = random_scalar(&order);
+                assert_eq!(invert_mod_prime(&x, &order).unwrap(), x.modinv(&order).unwrap_or(BigInt::ZERO));
+            }
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_prime_too_wide() {
+        //y^2 = x^3 + 1 over the Mersenne prime 2^607 - 1, too wide for the fixed-width types
+        let p: BigInt = (BigInt::from(1) << 607) - 1;
+        let curve = EC::new(BigInt::ZERO, BigInt::from(1), p.clone());
+        let point = affine(0, 1);
+
+        assert_eq!(curve.point_n_addition(point.clone(), BigInt::from(3)), Err(FixedWidthError::PrimeTooWide));
+        assert_eq!(invert_mod_prime(&BigInt::from(3), &p), Err(FixedWidthError::PrimeTooWide));
+
+        //the variable-time path has no size limit
+        assert_eq!(curve.point_n_addition_vartime(point, BigInt::from(3)), Point::Identity);
+    }
+
+    #[test]
+    #[should_panic(expected = "p must be at most 576 bits")]
+    fn test_domain_parameters_prime_too_wide() {
+        let p: BigInt = (BigInt::from(1) << 607) - 1;
+        let curve = EC::new(BigInt::ZERO, BigInt::from(1), p);
+        DomainParameters::new(curve, affine(0, 1), BigInt::from(3), BigInt::from(1));
+    }
+
+    #[test]
+    fn test_scalar_multiplication() {
+        let (curve, g) = small_curve();
+        let order = BigInt::from(19);
+
+        assert_eq!(curve.point_n_addition(g.clone(), BigInt::ZERO).unwrap(), Point::Identity);
+        assert_eq!(curve.point_n_addition(g.clone(), BigInt::from(1)).unwrap(), g);
+        assert_eq!(curve.point_n_addition(g.clone(), order.clone()).unwrap(), Point::Identity);
+        assert_eq!(curve.point_n_addition(g.clone(), &order + BigInt::from(1)).unwrap(), g);
+        assert_eq!(curve.point_n_addition(g.clone(), BigInt::from(-1)).unwrap(), curve.negate(&g));
+        assert_eq!(curve.point_n_addition(Point::Identity, BigInt::from(7)).unwrap(), Point::Identity);
+
+        //n*G agrees with repeated addition for every n up to the order
+        let mut expected = Point::Identity;
+        for n in 0..=19 {
+            assert_eq!(curve.point_n_addition(g.clone(), BigInt::from(n)).unwrap(), expected);
+            expected = curve.point_addition(&expected, &g);
+        }
+    }
+
+    fn p256() -> (EC, Point, BigInt) {
+        let domain = NamedCurve::P256.domain_parameters();
+        (domain.curve().clone(), domain.generator().clone(), domain.order().clone())
+    }
+
+    fn random_scalar(order: &BigInt) -> BigInt {
+        let (_, bound) = order.clone().into_parts();
+        BigInt::from(crate::rng::generate_random_less_than(&bound))
+    }
+
+    #[test]
+    fn test_vartime_scalar_multiplication() {
+        let (curve, g) = small_curve();
+
+        let mut expected = Point::Identity;
+        for n in 0..=20 {
+            assert_eq!(curve.point_n_addition_vartime(g.clone(), BigInt::from(n)), expected);
+            expected = curve.point_addition(&expected, &g);
+        }
+        assert_eq!(curve.point_n_addition_vartime(g.clone(), BigInt::from(-1)), curve.negate(&g));
+    }
+
+    #[test]
+    fn test_p256_scalar_multiplication() {
+        //the ladder and the Jacobian double and add, checked against the affine group law
+        let (curve, g, order) = p256();
+
+        let mut expected = Point::Identity;
+        for n in 0..=16 {
+            assert_eq!(curve.point_n_addition(g.clone(), BigInt::from(n)).unwrap(), expected);
+            assert_eq!(curve.point_n_addition_vartime(g.clone(), BigInt::from(n)), expected);
+            expected = curve.point_addition(&expected, &g);
+        }
+
+        //(n-1)*G = -G and n*G = O
+        let scalar_multiplications: [fn(&EC, Point, BigInt) -> Point; 2] = [
+            |curve, point, n| curve.point_n_addition(point, n).unwrap(),
+            EC::point_n_addition_vartime
+        ];
+        for scalar_multiplication in scalar_multiplications {
+            assert_eq!(scalar_multiplication(&curve, g.clone(), &order - BigInt::from(1)), curve.negate(&g));
+            assert_eq!(scalar_multiplication(&curve, g.clone(), order.clone()), Point::Identity);
+        }
+
+        //a*(b*G) = (a*b)*G, with both algorithms agreeing on every intermediate point
+        for _ in 0..4 {
+            let (a, b) = (random_scalar(&order), random_scalar(&order));
+            let b_g = curve.point_n_addition(g.clone(), b.clone()).unwrap();
+            assert_eq!(curve.point_n_addition_vartime(g.clone(), b.clone()), b_g);
+
+            let a_b_g = curve.point_n_addition(b_g.clone(), a.clone()).unwrap();
+            assert_eq!(curve.point_n_addition_vartime(b_g, a.clone()), a_b_g);
+            assert_eq!(curve.point_n_addition(g.clone(), a * b).unwrap(), a_b_g);
+        }
+    }
+
+    //dudect-style leakage test (Reparaz, Balasch and Verbauwhede 2017): time the ladder
+    //on a fixed scalar against fresh random scalars, in random interleaved order, and
+    //apply Welch's t-test to the two timing distributions after cropping the slowest
+    //measurements. |t| > 10 is a clear leak. as a control, the same harness must flag
+    //the variable-time double and add.
+    //
+    //the sample count is kept small so this runs with the rest of the suite. the
+    //control still separates cleanly at this size, and the ladder has enough headroom
+    //below the threshold that scheduler noise doesn't fail it
+    #[test]
+    fn test_ladder_timing() {
+        let (curve, g, order) = p256();
+        let samples = 500;
+
+        //low Hamming weight fixed scalar, the worst case for double and add
+        let fixed = BigInt::from(1) << 200;
+
+        let t_ladder = welch_t_statistic(samples, &fixed, &order, |n| curve.point_n_addition(g.clone(), n).unwrap());
+        let t_vartime = welch_t_statistic(samples, &fixed, &order, |n| curve.point_n_addition_vartime(g.clone(), n));
+
+        assert!(t_vartime.abs() > 10.0, "harness failed to detect double and add leak: t = {}", t_vartime);
+        assert!(t_ladder.abs() < 10.0, "ladder timing depends on the scalar: t = {}", t_ladder);
+    }
+
+    fn welch_t_statistic<F: Fn(BigInt) -> Point>(samples: usize, fixed: &BigInt, order: &BigInt, f: F) -> f64 {
+        use rand::Rng;
+        let mut rng = rand::rng();
+        let mut timings: Vec<(bool, f64)> = Vec::with_capacity(samples);
+
+        for _ in 0..samples {
+            let class = rng.random::<bool>();
+            let scalar = if class { random_scalar(order) } else { fixed.clone() };
+
+            let start = std::time::Instant::now();
+            std::hint::black_box(f(std::hint::black_box(scalar)));
+            timings.push((class, start.elapsed().as_nanos() as f64));
+        }
+
+        //crop measurements above the 90th percentile, which are mostly interrupts
+        let mut sorted: Vec<f64> = timings.iter().map(|(_, t)| *t).collect();
+        sorted.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        let threshold = sorted[samples * 9 / 10];
+
+        let moments = |class: bool| {
+            let values: Vec<f64> = timings.iter()
+                .filter(|(c, t)| *c == class && *t <= threshold)
+                .map(|(_, t)| *t)
+                .collect();
+            let count = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / count;
+            let variance = values.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (count - 1.0);
+            (mean, variance, count)
+        };
+
+        let (mean_0, variance_0, count_0) = moments(false);
+        let (mean_1, variance_1, count_1) = moments(true);
+
+        (mean_0 - mean_1) / (variance_0 / count_0 + variance_1 / count_1).sqrt()
+    }
+
+    #[test]
+    fn test_named_curves() {
+        for named_curve in NamedCurve::ALL {
+            //construction checks G is on the curve, n is prime and n*G = O
+            let domain = named_curve.domain_parameters();
+            let (curve, g, n) = (domain.curve(), domain.generator(), domain.order());
+
+            assert_eq!(NamedCurve::from_name(named_curve.name()), Some(named_curve));
+            assert_eq!(domain.cofactor(), &BigInt::from(1));
+            assert_eq!(named_curve.domain_parameters(), domain);
+
+            //(n+1)*G = G via the ladder
+            assert_eq!(curve.point_n_addition(g.clone(), n + BigInt::from(1)).unwrap(), *g);
+        }
+
+        //d*G on each curve for d = RFC 6979's P-256 test key, from OpenSSL
+        let d = BigInt::parse_bytes(b"c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721", 16).unwrap();
+        let expected = [
+            (NamedCurve::P256,
+             "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+             "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"),
+            (NamedCurve::P384,
+             "7c230d20b5acb84e2751245cfea6c662892bcf8486a018127aa5e16049a6fdd8ab5326d0f69b5b708eb595ab4ed6ef6",
+             "5577b0db3bcbd4a0a84306e0336ef65c3b79949f873b5b34d1b668feeb44143a25bfe079dc4bec36e82e87ae642419e9"),
+            (NamedCurve::P521,
+             "8d350b66b953da1a1d2d3eaac4bdf57f01504a72fd8f9cb9ec042851e155a343abcba5f738758d0c1564eae62b18becfd0d79f6a22e9e63f54d95abb4ae01a27c4",
+             "3932771c969208f6c046475e99d1ccc8be2604c4cf9d2c2d8799ccda09b94ee063bb70f0332b2d66d47dd3a6a25c217bc826d3ec2cf871622c4528b7121018a331"),
+            (NamedCurve::Secp256k1,
+             "2c8c31fc9f990c6b55e3865a184a4ce50e09481f2eaeb3e60ec1cea13a6ae645",
+             "64b95e4fdb6948c0386e189b006a29f686769b011704275e4459822dc3328085"),
+        ];
+        for (named_curve, x, y) in expected {
+            let domain = named_curve.domain_parameters();
+            let q = domain.curve().point_n_addition(domain.generator().clone(), d.clone()).unwrap();
+            let expected = Point::Affine(
+                BigInt::parse_bytes(x.as_bytes(), 16).unwrap(),
+                BigInt::parse_bytes(y.as_bytes(), 16).unwrap()
+            );
+            assert_eq!(q, expected, "{}", named_curve.name());
+        }
+
+        assert_eq!(NamedCurve::from_name("prime256v1"), Some(NamedCurve::P256));
+        assert_eq!(NamedCurve::from_name("SECP384R1"), Some(NamedCurve::P384));
+        assert_eq!(NamedCurve::from_name("curve25519"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "generator order n must be prime")]
+    fn test_composite_order_rejected() {
+        //E: y^2 = x^3 - x mod 23 has 24 points and (10, 1) has order 12
+        let curve = EC::new(BigInt::from(-1), BigInt::ZERO, BigInt::from(23));
+        let g = affine(10, 1);
+        assert!(curve.point_n_addition_vartime(g.clone(), BigInt::from(12)).is_identity());
+
+        DomainParameters::new(curve, g, BigInt::from(12), BigInt::from(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "n*G must be the point at infinity")]
+    fn test_wrong_order_rejected() {
+        let (curve, g) = small_curve();
+        DomainParameters::new(curve, g, BigInt::from(17), BigInt::from(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not lie on E")]
+    fn test_point_not_on_curve() {
+        let (curve, g) = small_curve();
+        curve.point_addition(&g, &affine(5, 2));
+    }
+
+    #[test]
+    fn test_sec1_small_curve() {
+        //every point of the mod 17 curve survives both encodings
+        let (curve, g) = small_curve();
+        for n in 0..19 {
+            let point = curve.point_n_addition(g.clone(), BigInt::from(n)).unwrap();
+            for compressed in [true, false] {
+                let bytes = point.to_sec1_bytes(&curve, compressed);
+                assert_eq!(Point::from_sec1_bytes(&curve, &bytes), Ok(point.clone()));
+            }
+        }
+
+        assert_eq!(g.to_sec1_bytes(&curve, true), vec![0x03, 5]);
+        assert_eq!(g.to_sec1_bytes(&curve, false), vec![0x04, 5, 1]);
+        assert_eq!(Point::Identity.to_sec1_bytes(&curve, true), vec![0x00]);
+    }
+
+    #[test]
+    fn test_sec1_named_curves() {
+        //d*G for d = 0xc0ffee, from Python cryptography (X9.62 point formats)
+        let expected = [
+            (NamedCurve::P256,
+             "02d360332fad9bc83afaff4a740de8a516bf1b8fb3fde360ff1d03979c1f943ee2",
+             "04d360332fad9bc83afaff4a740de8a516bf1b8fb3fde360ff1d03979c1f943ee2e8a66007fd276b0271265c6db092c4a0c5eb8c45fdc436502c8a095f5d5745f2"),
+            (NamedCurve::P384,
+             "0232ee5c42b2fe56347203b8edea84419f840701404d49c35da9b795ee000905419452be10623260b87ccc60aafff64239",
+             "0432ee5c42b2fe56347203b8edea84419f840701404d49c35da9b795ee000905419452be10623260b87ccc60aafff64239db3707273dda8747305f8c8e717b60081dc3ecd95b091d85e7a1c5672c5cd675336ecf789942c308e6c39226fcc0949c"),
+            (NamedCurve::P521,
+             "0301b1fdd5c9c169a37b5f82ca15627b61cf270262c76709563e09413acd76d328c24d96869d22119a7c4facdb82eeccee3fee68db2e2a264a6d48720c39139662628a",
+             "0401b1fdd5c9c169a37b5f82ca15627b61cf270262c76709563e09413acd76d328c24d96869d22119a7c4facdb82eeccee3fee68db2e2a264a6d48720c39139662628a01edf8d1dbb9813d82972d3b8fd8b340bbb5640929bfca1f262ac882fe465db6151323283c90a5788e9766e3279ffe4f2259867e87cc77010314d677110a6427f51f"),
+            (NamedCurve::Secp256k1,
+             "032a5bbcb0eede528e6abe5f2ec50ad7887eb5677af383a460b05ee23bf892dfe5",
+             "042a5bbcb0eede528e6abe5f2ec50ad7887eb5677af383a460b05ee23bf892dfe552c93747550eda8404c8b473786c00dfd8fd1ef4bc033f359ccf5b77bd656d21"),
+        ];
+
+        for (named_curve, compressed, uncompressed) in expected {
+            let domain = named_curve.domain_parameters();
+            let curve = domain.curve();
+            let q = curve.point_n_addition(domain.generator().clone(), BigInt::from(0xc0ffee)).unwrap();
+
+            assert_eq!(hex::encode(q.to_sec1_bytes(curve, true)), compressed, "{}", named_curve.name());
+            assert_eq!(hex::encode(q.to_sec1_bytes(curve, false)), uncompressed, "{}", named_curve.name());
+            assert_eq!(Point::from_sec1_bytes(curve, &hex::decode(compressed).unwrap()), Ok(q.clone()));
+            assert_eq!(Point::from_sec1_bytes(curve, &hex::decode(uncompressed).unwrap()), Ok(q));
+        }
+    }
+
+    #[test]
+    fn test_sec1_invalid_encodings() {
+        let domain = NamedCurve::P256.domain_parameters();
+        let curve = domain.curve();
+        let g = domain.generator().to_sec1_bytes(curve, false);
+
+        assert_eq!(Point::from_sec1_bytes(curve, &[]), Err(EncodingError::InvalidLength));
+        assert_eq!(Point::from_sec1_bytes(curve, &g[..33]), Err(EncodingError::InvalidLength));
+        assert_eq!(Point::from_sec1_bytes(curve, &[0x00, 0x00]), Err(EncodingError::InvalidLength));
+
+        let mut hybrid = g.clone();
+        hybrid[0] = 0x07;
+        assert_eq!(Point::from_sec1_bytes(curve, &hybrid), Err(EncodingError::InvalidTag));
+
+        let mut off_curve = g.clone();
+        off_curve[64] ^= 1;
+        assert_eq!(Point::from_sec1_bytes(curve, &off_curve), Err(EncodingError::PointNotOnCurve));
+
+        //x = p doesn't fit in the field even though it is 32 bytes
+        let mut out_of_range = vec![0x02];
+        out_of_range.extend_from_slice(&to_fixed_bytes(curve.p(), 32));
+        assert_eq!(Point::from_sec1_bytes(curve, &out_of_range), Err(EncodingError::CoordinateOutOfRange));
+
+        //x = 1 gives 1 - 3 + b, a non-residue mod the P-256 prime, so no point has x = 1
+        let mut no_root = vec![0x02];
+        no_root.extend_from_slice(&to_fixed_bytes(&BigInt::from(1), 32));
+        assert_eq!(Point::from_sec1_bytes(curve, &no_root), Err(EncodingError::PointNotOnCurve));
+    }
+}
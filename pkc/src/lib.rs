@@ -5,3 +5,7 @@ pub mod elliptic_curve_primitives;
 pub mod ecdh_key_exchange;
 pub mod digital_signature_algorithm;
 pub mod ecdsa;
+pub mod rng;
+pub mod rfc6979;
+pub mod x25519;
+pub mod ed25519;
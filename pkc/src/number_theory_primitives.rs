@@ -1,5 +1,18 @@
 use std::ops::{Add, Div, Rem};
-use num_bigint::BigInt;
+use num_bigint::{BigInt, Sign};
+use rand::CryptoRng;
+use crate::rng;
+
+//miller-rabin rounds used when generating primes, giving an error bound of 2^-80
+pub const PRIME_GENERATION_ROUNDS: usize = 40;
+
+//primes below 256, used for trial division before running miller-rabin
+const SMALL_PRIMES: [u32; 54] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+    73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151,
+    157, 163, 167, 173, 179, 181, 191, 193, 197, 199, 211, 223, 227, 229, 233,
+    239, 241, 251
+];
 
 //by fundamental theorem of arithmetic
 pub fn prime_factorsation(mut n:BigInt) -> Vec<(BigInt, BigInt)> {
@@ -88,11 +101,11 @@ pub fn gcd(a:BigInt, b:BigInt) -> BigInt {
     };
 
     if b == BigInt::ZERO {
-        return a
+        a
     } else {
         let b_clone = b.clone();
-        return gcd(b, a % b_clone)
-    };
+        gcd(b, a % b_clone)
+    }
 }
 
 //consequence
@@ -164,11 +177,602 @@ pub fn costly_modular_exponentiation(a:BigInt, e:BigInt, n:BigInt) -> BigInt {
     BigInt::modpow(&a, &e, &n)
 }
 
-//efficient modular exponentiation
-pub fn montgomery_modular_exponentiation(a:BigInt, e:BigInt, n:BigInt) {}
+//modular exponentiation with montgomery multiplication
+//
+//montgomery multiplication needs an odd modulus n > 1 of at most
+//MONTGOMERY_MAX_BITS, so every other modulus falls back to
+//costly_modular_exponentiation. for n = 1 that gives 0, as it does there.
+//callers exponentiating repeatedly with the same modulus should build a
+//MontgomeryContext once and call its modpow instead
+pub fn montgomery_modular_exponentiation(a:BigInt, e:BigInt, n:BigInt) -> BigInt {
+    match MontgomeryContext::new(&n) {
+        Some(context) => context.modpow(&a, &e),
+        None => costly_modular_exponentiation(a, e, n)
+    }
+}
+
+//widest modulus a MontgomeryContext supports
+pub const MONTGOMERY_MAX_BITS: u64 = 8192;
+
+///precomputed values for montgomery arithmetic modulo an odd n > 1
+///
+///residues live in fixed-width arrays of 64-bit limbs, sized to the smallest of 256,
+///512, 1024, 2048, 3072, 4096 or 8192 bits that holds n. with R = 2^width a residue a
+///is held in montgomery form aR mod n, and products are reduced limb by limb without
+///any division by n. the setup and the exponentiation run in time that
+///depends only on the width, not on the values of n, a or e, so the context can be
+///used with secret moduli (the rsa primes) and secret exponents.
+///
+/// fields:
+///     - n = odd modulus
+///     - limbs = n and its montgomery constants at the chosen width
+#[derive(Debug, Clone)]
+pub struct MontgomeryContext {
+    n: BigInt,
+    limbs: MontgomeryLimbs
+}
+
+//boxed, as the widest variant is several kilobytes
+#[derive(Debug, Clone)]
+enum MontgomeryLimbs {
+    L4(Box<FixedWidthModulus<4>>),
+    L8(Box<FixedWidthModulus<8>>),
+    L16(Box<FixedWidthModulus<16>>),
+    L32(Box<FixedWidthModulus<32>>),
+    L48(Box<FixedWidthModulus<48>>),
+    L64(Box<FixedWidthModulus<64>>),
+    L128(Box<FixedWidthModulus<128>>)
+}
+
+//runs the same generic expression for whichever width the context was built with
+macro_rules! with_limbs {
+    ($limbs:expr, $m:ident => $body:expr) => {
+        match $limbs {
+            MontgomeryLimbs::L4($m) => $body,
+            MontgomeryLimbs::L8($m) => $body,
+            MontgomeryLimbs::L16($m) => $body,
+            MontgomeryLimbs::L32($m) => $body,
+            MontgomeryLimbs::L48($m) => $body,
+            MontgomeryLimbs::L64($m) => $body,
+            MontgomeryLimbs::L128($m) => $body
+        }
+    };
+}
+
+impl MontgomeryContext {
+    //returns None unless n is odd, greater than 1 and at most MONTGOMERY_MAX_BITS wide
+    pub fn new(n: &BigInt) -> Option<Self> {
+        if n <= &BigInt::from(1) || !n.bit(0) || n.bits() > MONTGOMERY_MAX_BITS {
+            return None
+        };
+
+        let limbs: MontgomeryLimbs = match n.bits() {
+            0..=256 => MontgomeryLimbs::L4(Box::new(FixedWidthModulus::new(n))),
+            257..=512 => MontgomeryLimbs::L8(Box::new(FixedWidthModulus::new(n))),
+            513..=1024 => MontgomeryLimbs::L16(Box::new(FixedWidthModulus::new(n))),
+            1025..=2048 => MontgomeryLimbs::L32(Box::new(FixedWidthModulus::new(n))),
+            2049..=3072 => MontgomeryLimbs::L48(Box::new(FixedWidthModulus::new(n))),
+            3073..=4096 => MontgomeryLimbs::L64(Box::new(FixedWidthModulus::new(n))),
+            _ => MontgomeryLimbs::L128(Box::new(FixedWidthModulus::new(n)))
+        };
+
+        Some(Self { n: n.clone(), limbs })
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.n
+    }
+
+    //a -> aR mod n
+    pub fn to_montgomery(&self, a: &BigInt) -> BigInt {
+        let a: BigInt = self.reduce(a);
+        with_limbs!(&self.limbs, m => from_limbs(&m.montgomery_form(&to_limbs(&a))))
+    }
+
+    //aR -> a mod n, aR must already be reduced mod n
+    pub fn from_montgomery(&self, a: &BigInt) -> BigInt {
+        with_limbs!(&self.limbs, m => from_limbs(&m.standard_form(&to_limbs(a))))
+    }
+
+    //(aR, bR) -> abR mod n, both already reduced mod n
+    pub fn multiply(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        with_limbs!(&self.limbs, m => from_limbs(&m.multiply(&to_limbs(a), &to_limbs(b))))
+    }
+
+    //computes a^e mod n, taking the same time for every e up to the width of n
+    pub fn modpow(&self, a: &BigInt, e: &BigInt) -> BigInt {
+        self.modpow_bounded(a, e, self.n.bits())
+    }
+
+    //computes a^e mod n, taking the same time for every e < 2^exponent_bits. the bound
+    //is public, so a short secret exponent (a DSA nonce below q) in a wide group doesn't
+    //pay for the full width of n
+    pub fn modpow_bounded(&self, a: &BigInt, e: &BigInt, exponent_bits: u64) -> BigInt {
+        assert!(e >= &BigInt::ZERO, "exponent must be non-negative");
+
+        let a: BigInt = self.reduce(a);
+        let exponent_limbs: usize = exponent_bits.div_ceil(64) as usize;
+        with_limbs!(&self.limbs, m => from_limbs(&m.modpow(&to_limbs(&a), e, exponent_limbs)))
+    }
+
+    //a mod n in [0, n)
+    fn reduce(&self, a: &BigInt) -> BigInt {
+        let mut a: BigInt = a.rem(&self.n);
+        if a < BigInt::ZERO {
+            a += &self.n;
+        }
+        a
+    }
+}
+
+///odd modulus n < R = 2^(64 * LIMBS) with its montgomery constants, limbs little endian
+///
+/// fields:
+///     - n = modulus
+///     - n_prime = -n^-1 mod 2^64
+///     - one = R mod n, i.e. 1 in montgomery form
+///     - r_squared = R^2 mod n, used to move numbers into montgomery form
+#[derive(Debug, Clone)]
+struct FixedWidthModulus<const LIMBS: usize> {
+    n: [u64; LIMBS],
+    n_prime: u64,
+    one: [u64; LIMBS],
+    r_squared: [u64; LIMBS]
+}
+
+//4-bit fixed window for modpow
+const MONTGOMERY_WINDOW: usize = 4;
+
+impl<const LIMBS: usize> FixedWidthModulus<LIMBS> {
+    //n must be odd and fit in LIMBS limbs
+    fn new(n: &BigInt) -> Self {
+        let n: [u64; LIMBS] = to_limbs(n);
+
+        //newton iteration for n^-1 mod 2^64, each step doubles the correct low bits
+        let mut n_inv: u64 = 1;
+        for _ in 0..6 {
+            n_inv = n_inv.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(n_inv)));
+        }
+
+        //R mod n and R^2 mod n by repeated doubling of 1, so the setup never divides
+        //by the (possibly secret) modulus
+        let mut modulus = Self { n, n_prime: n_inv.wrapping_neg(), one: [0; LIMBS], r_squared: [0; LIMBS] };
+        let mut x: [u64; LIMBS] = [0; LIMBS];
+        x[0] = 1;
+        for _ in 0..64 * LIMBS {
+            x = modulus.double(&x);
+        }
+        modulus.one = x;
+        for _ in 0..64 * LIMBS {
+            x = modulus.double(&x);
+        }
+        modulus.r_squared = x;
+
+        modulus
+    }
+
+    //2x mod n for x < n
+    fn double(&self, x: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut doubled: [u64; LIMBS] = [0; LIMBS];
+        let mut carry: u64 = 0;
+        for (d, &limb) in doubled.iter_mut().zip(x) {
+            *d = (limb << 1) | carry;
+            carry = limb >> 63;
+        }
+
+        self.subtract_if_not_below(&doubled, carry)
+    }
+
+    //for t + 2^(64 * LIMBS) * high < 2n returns the value mod n, without branching
+    fn subtract_if_not_below(&self, t: &[u64; LIMBS], high: u64) -> [u64; LIMBS] {
+        let mut difference: [u64; LIMBS] = [0; LIMBS];
+        let mut borrow: u64 = 0;
+        for ((d, &t_j), &n_j) in difference.iter_mut().zip(t).zip(&self.n) {
+            let (d_1, b_1) = t_j.overflowing_sub(n_j);
+            let (d_2, b_2) = d_1.overflowing_sub(borrow);
+            *d = d_2;
+            borrow = (b_1 | b_2) as u64;
+        }
+
+        //keep t only when it was below n, i.e. the subtraction borrowed out of high
+        let (_, below) = high.overflowing_sub(borrow);
+        let keep: u64 = (below as u64).wrapping_neg();
+        let mut result: [u64; LIMBS] = [0; LIMBS];
+        for ((r, &t_j), &d_j) in result.iter_mut().zip(t).zip(&difference) {
+            *r = (t_j & keep) | (d_j & !keep);
+        }
+        result
+    }
+
+    //montgomery multiplication: (aR, bR) -> abR mod n for a, b < n
+    //
+    //each pass adds a_i * b and then the multiple of n that clears the lowest limb,
+    //so after LIMBS passes the low half is zero and the high half is abR^-1 < 2n
+    fn multiply(&self, a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut wide: [[u64; LIMBS]; 2] = [[0; LIMBS]; 2];
+        let t: &mut [u64] = wide.as_flattened_mut();
+        let mut carry: u64 = 0;
+
+        for (i, &a_i) in a.iter().enumerate() {
+            let carry_1: u64 = add_multiple(&mut t[i..i + LIMBS], b, a_i);
+            let m: u64 = t[i].wrapping_mul(self.n_prime);
+            let carry_2: u64 = add_multiple(&mut t[i..i + LIMBS], &self.n, m);
+
+            let (sum, overflow_1) = carry.overflowing_add(carry_1);
+            let (sum, overflow_2) = sum.overflowing_add(carry_2);
+            t[i + LIMBS] = sum;
+            carry = (overflow_1 | overflow_2) as u64;
+        }
+
+        self.subtract_if_not_below(&wide[1], carry)
+    }
+
+    //montgomery squaring: aR -> a^2 R mod n for a < n
+    //
+    //squaring dominates modpow, and a square only needs about half the limb products
+    //of a multiplication: the cross terms a_i a_j for i < j are summed once and doubled
+    fn square(&self, a: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut wide: [[u64; LIMBS]; 2] = [[0; LIMBS]; 2];
+        let t: &mut [u64] = wide.as_flattened_mut();
+
+        // 1. cross terms
+        for i in 0..LIMBS - 1 {
+            t[i + LIMBS] = add_multiple(&mut t[2 * i + 1..i + LIMBS], &a[i + 1..], a[i]);
+        }
+
+        // 2. double them
+        let mut shifted_out: u64 = 0;
+        for limb in t.iter_mut() {
+            let top: u64 = *limb >> 63;
+            *limb = (*limb << 1) | shifted_out;
+            shifted_out = top;
+        }
+
+        // 3. add the squares a_i^2 on the diagonal
+        let mut carry: u64 = 0;
+        for (i, &a_i) in a.iter().enumerate() {
+            let product: u128 = a_i as u128 * a_i as u128;
+            let sum: u128 = t[2 * i] as u128 + (product as u64) as u128 + carry as u128;
+            t[2 * i] = sum as u64;
+            let sum: u128 = t[2 * i + 1] as u128 + (product >> 64) + (sum >> 64);
+            t[2 * i + 1] = sum as u64;
+            carry = (sum >> 64) as u64;
+        }
+
+        // 4. clear the low half one limb at a time, as in multiply
+        let mut carry: u64 = 0;
+        for i in 0..LIMBS {
+            let m: u64 = t[i].wrapping_mul(self.n_prime);
+            let carry_1: u64 = add_multiple(&mut t[i..i + LIMBS], &self.n, m);
+            let sum: u128 = t[i + LIMBS] as u128 + carry_1 as u128 + carry as u128;
+            t[i + LIMBS] = sum as u64;
+            carry = (sum >> 64) as u64;
+        }
+
+        self.subtract_if_not_below(&wide[1], carry)
+    }
+
+    //a -> aR mod n for a < n
+    fn montgomery_form(&self, a: &[u64; LIMBS]) -> [u64; LIMBS] {
+        self.multiply(a, &self.r_squared)
+    }
+
+    //aR -> a mod n
+    fn standard_form(&self, a: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut unit: [u64; LIMBS] = [0; LIMBS];
+        unit[0] = 1;
+        self.multiply(a, &unit)
+    }
+
+    //a^e mod n for a < n with a fixed 4-bit window
+    //
+    //the exponent is padded to at least exponent_limbs limbs and every window is
+    //multiplied in, with the table entry picked by a masked scan, so the time depends
+    //only on the padded length
+    fn modpow(&self, a: &[u64; LIMBS], e: &BigInt, exponent_limbs: usize) -> [u64; LIMBS] {
+        //table[i] = a^i in montgomery form
+        let mut table: [[u64; LIMBS]; 1 << MONTGOMERY_WINDOW] = [self.one; 1 << MONTGOMERY_WINDOW];
+        table[1] = self.montgomery_form(a);
+        for i in 2..table.len() {
+            table[i] = self.multiply(&table[i - 1], &table[1]);
+        }
+
+        let mut exponent: Vec<u64> = e.iter_u64_digits().collect();
+        exponent.resize(exponent.len().max(exponent_limbs).max(1), 0);
+
+        let mut result: [u64; LIMBS] = self.one;
+        for limb in exponent.iter().rev() {
+            for window in (0..64 / MONTGOMERY_WINDOW).rev() {
+                for _ in 0..MONTGOMERY_WINDOW {
+                    result = self.square(&result);
+                }
+
+                let digit: usize = ((limb >> (window * MONTGOMERY_WINDOW)) & ((1 << MONTGOMERY_WINDOW) - 1)) as usize;
+                let entry: [u64; LIMBS] = select_entry(&table, digit);
+                result = self.multiply(&result, &entry);
+            }
+        }
+
+        self.standard_form(&result)
+    }
+}
+
+//z += x * y over the length of x, returning the carry out of the top limb
+fn add_multiple(z: &mut [u64], x: &[u64], y: u64) -> u64 {
+    let mut carry: u64 = 0;
+    for (z_i, &x_i) in z.iter_mut().zip(x) {
+        let product: u128 = x_i as u128 * y as u128 + *z_i as u128;
+        let (low, overflow) = (product as u64).overflowing_add(carry);
+        *z_i = low;
+        carry = (product >> 64) as u64 + overflow as u64;
+    }
+    carry
+}
+
+//table[index], reading every entry so the memory access pattern doesn't leak index
+fn select_entry<const LIMBS: usize>(table: &[[u64; LIMBS]], index: usize) -> [u64; LIMBS] {
+    let mut entry: [u64; LIMBS] = [0; LIMBS];
+    for (i, candidate) in table.iter().enumerate() {
+        //all ones when i == index, without a comparison branch
+        let difference: u64 = (i ^ index) as u64;
+        let mask: u64 = ((difference | difference.wrapping_neg()) >> 63).wrapping_sub(1);
+        for (e, &c) in entry.iter_mut().zip(candidate) {
+            *e |= c & mask;
+        }
+    }
+    entry
+}
+
+//x must be non-negative and fit in LIMBS limbs
+fn to_limbs<const LIMBS: usize>(x: &BigInt) -> [u64; LIMBS] {
+    let mut limbs: [u64; LIMBS] = [0; LIMBS];
+    for (limb, digit) in limbs.iter_mut().zip(x.iter_u64_digits()) {
+        *limb = digit;
+    }
+    limbs
+}
+
+fn from_limbs<const LIMBS: usize>(x: &[u64; LIMBS]) -> BigInt {
+    let bytes: Vec<u8> = x.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+    BigInt::from_bytes_le(Sign::Plus, &bytes)
+}
+
+//Legendre symbol (a/p) for an odd prime p, by Euler's criterion a^((p-1)/2) mod p:
+//  0 if p | a, 1 if a is a nonzero square mod p and -1 otherwise
+pub fn legendre_symbol(a: &BigInt, p: &BigInt) -> i32 {
+    assert!(p > &BigInt::from(2) && p.bit(0), "legendre symbol needs an odd prime p");
+
+    let a: BigInt = ((a % p) + p) % p;
+    let euler: BigInt = a.modpow(&((p - BigInt::from(1)) >> 1), p);
+
+    if euler == BigInt::ZERO {
+        0
+    } else if euler == BigInt::from(1) {
+        1
+    } else {
+        -1
+    }
+}
+
+//Jacobi symbol (a/n) for any odd n > 0, the product of the legendre symbols over the
+//prime factors of n. computed without factoring n by quadratic reciprocity:
+//  1. (2/n) = -1 exactly when n = 3 or 5 (mod 8)
+//  2. (a/n) = -(n/a) when a = n = 3 (mod 4), and (a/n) = (n/a) otherwise
+//(a/n) = -1 proves a is not a square mod n, but (a/n) = 1 does not prove it is
+pub fn jacobi_symbol(a: &BigInt, n: &BigInt) -> i32 {
+    assert!(n > &BigInt::ZERO && n.bit(0), "jacobi symbol needs an odd positive n");
+
+    let mut a: BigInt = ((a % n) + n) % n;
+    let mut n: BigInt = n.clone();
+    let mut result: i32 = 1;
+
+    while a != BigInt::ZERO {
+        let twos: u64 = a.trailing_zeros().unwrap();
+        a >>= twos;
+        let n_mod_8: BigInt = (&n).rem(BigInt::from(8));
+        if twos % 2 == 1 && (n_mod_8 == BigInt::from(3) || n_mod_8 == BigInt::from(5)) {
+            result = -result;
+        }
+
+        (a, n) = (n, a);
+        if a.bit(1) && n.bit(1) {
+            result = -result;
+        }
+        a = (&a).rem(&n);
+    }
+
+    if n == BigInt::from(1) {
+        return result
+    }
+    0
+}
+
+//square root of a modulo a prime p: returns some x with x^2 = a (mod p), or None
+//when a is not a square. the other root is p - x
+//
+//odd primes with p = 3 (mod 4) take x = a^((p+1)/4). the rest go through the
+//Tonelli-Shanks algorithm, which writes p - 1 = q * 2^s with q odd and repairs
+//the guess a^((q+1)/2) with powers of a non-residue z
+pub fn sqrt_mod_prime(a: &BigInt, p: &BigInt) -> Option<BigInt> {
+    let one: BigInt = BigInt::from(1);
+    let a: BigInt = ((a % p) + p) % p;
+
+    if p == &BigInt::from(2) || a == BigInt::ZERO {
+        return Some(a)
+    };
+    if legendre_symbol(&a, p) != 1 {
+        return None
+    };
+    if p.bit(1) {
+        return Some(a.modpow(&((p + &one) >> 2), p))
+    };
+
+    let p_minus_one: BigInt = p - &one;
+    let s: u64 = p_minus_one.trailing_zeros().unwrap();
+    let q: BigInt = &p_minus_one >> s;
+
+    //half of the residues are non-residues, so the search ends quickly
+    let mut z: BigInt = BigInt::from(2);
+    while legendre_symbol(&z, p) != -1 {
+        z += &one;
+    }
+
+    //invariant: x^2 = a*t (mod p), with the order of t dividing 2^(m-1)
+    let mut m: u64 = s;
+    let mut c: BigInt = z.modpow(&q, p);
+    let mut t: BigInt = a.modpow(&q, p);
+    let mut x: BigInt = a.modpow(&((&q + &one) >> 1), p);
+
+    while t != one {
+        //least i with t^(2^i) = 1
+        let mut i: u64 = 0;
+        let mut t_2i: BigInt = t.clone();
+        while t_2i != one {
+            t_2i = (&t_2i * &t_2i).rem(p);
+            i += 1;
+        }
+
+        let b: BigInt = c.modpow(&(&one << (m - i - 1)), p);
+        m = i;
+        c = (&b * &b).rem(p);
+        t = (t * &c).rem(p);
+        x = (x * b).rem(p);
+    }
+
+    Some(x)
+}
 
 //miller-rabin primality test
-pub fn is_prime() {}
+//
+//n is first trial divided by the primes below 256. each of the `rounds` rounds then
+//picks a random base a in [2, n-2], so a composite n survives with probability
+//at most 4^(-rounds)
+pub fn is_prime(n: &BigInt, rounds: usize) -> bool {
+    let one: BigInt = BigInt::from(1);
+    let two: BigInt = BigInt::from(2);
+
+    if n < &two {
+        return false
+    };
+
+    for small_prime in SMALL_PRIMES {
+        let small_prime = BigInt::from(small_prime);
+        if n == &small_prime {
+            return true
+        };
+        if does_divide(n, &small_prime) {
+            return false
+        };
+    }
+
+    //write n - 1 = 2^s * d with d odd
+    let n_minus_one: BigInt = n - &one;
+    let s: u64 = n_minus_one.trailing_zeros().unwrap();
+    let d: BigInt = &n_minus_one >> s;
+
+    //bases are drawn from [2, n-2]
+    let (_, base_range) = (n - BigInt::from(3)).into_parts();
+
+    'witness: for _ in 0..rounds {
+        let a: BigInt = BigInt::from_biguint(Sign::Plus, rng::generate_random_less_than(&base_range)) + &two;
+        let mut x: BigInt = a.modpow(&d, n);
+
+        if x == one || x == n_minus_one {
+            continue
+        };
+
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness
+            };
+        }
+
+        //a is a witness for the compositeness of n
+        return false
+    }
+
+    true
+}
+
+
+//random prime generation
+//
+//returns a random probable prime of exactly `bits` bits. the two most significant
+//bits are always set, so the product of two such primes has exactly 2*bits bits
+pub fn generate_prime<R: CryptoRng + ?Sized>(bits: u64, rng: &mut R) -> BigInt {
+    generate_prime_congruent(bits, &BigInt::from(1), &BigInt::from(2), rng)
+}
+
+//returns a random probable prime p of exactly `bits` bits with p = residue (mod modulus)
+//
+//e.g. DSA needs p = 1 (mod 2q). panics if gcd(residue, modulus) != 1, since then
+//no such prime of the requested size can exist
+pub fn generate_prime_congruent<R: CryptoRng + ?Sized>(bits: u64, residue: &BigInt, modulus: &BigInt, rng: &mut R) -> BigInt {
+    assert!(bits >= 2, "primes have at least 2 bits");
+    assert!(modulus > &BigInt::ZERO, "modulus must be positive");
+
+    let two: BigInt = BigInt::from(2);
+    let mut residue: BigInt = ((residue % modulus) + modulus) % modulus;
+    let mut modulus: BigInt = modulus.clone();
+
+    assert!(is_coprime(residue.clone(), modulus.clone()), "residue and modulus must be coprime");
+
+    //fold "p is odd" into the congruence by working mod lcm(2, modulus)
+    if !does_divide(&modulus, &two) {
+        if does_divide(&residue, &two) {
+            residue += &modulus;
+        }
+        modulus *= &two;
+    }
+
+    loop {
+        let candidate: BigInt = random_candidate(bits, &residue, &modulus, rng);
+
+        if candidate.bits() == bits && is_prime(&candidate, PRIME_GENERATION_ROUNDS) {
+            return candidate
+        };
+    }
+}
+
+//returns a random safe prime p = 2q + 1 of exactly `bits` bits, where q is also prime
+pub fn generate_safe_prime<R: CryptoRng + ?Sized>(bits: u64, rng: &mut R) -> BigInt {
+    assert!(bits >= 6, "safe primes are generated with at least 6 bits");
+
+    //every safe prime p > 7 satisfies p = 11 (mod 12)
+    let residue: BigInt = BigInt::from(11);
+    let modulus: BigInt = BigInt::from(12);
+
+    loop {
+        let candidate: BigInt = random_candidate(bits, &residue, &modulus, rng);
+        let q: BigInt = &candidate >> 1;
+
+        if candidate.bits() != bits {
+            continue
+        };
+
+        //sieve both numbers before spending miller-rabin rounds on either
+        if !is_prime(&q, 0) || !is_prime(&candidate, 0) {
+            continue
+        };
+
+        if is_prime(&q, PRIME_GENERATION_ROUNDS) && is_prime(&candidate, PRIME_GENERATION_ROUNDS) {
+            return candidate
+        };
+    }
+}
+
+//random integer with the top two bits of a `bits` bit number set, moved down onto
+//the residue class residue (mod modulus). may fall short of `bits` bits when the
+//modulus is large, so callers check the size
+fn random_candidate<R: CryptoRng + ?Sized>(bits: u64, residue: &BigInt, modulus: &BigInt, rng: &mut R) -> BigInt {
+    let mut candidate: BigInt = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits, rng));
+    candidate.set_bit(bits - 1, true);
+    candidate.set_bit(bits - 2, true);
+
+    let offset: BigInt = (&candidate).rem(modulus);
+    candidate - offset + residue
+}
 
 
 pub fn does_divide(p: &BigInt,q:&BigInt) -> bool {
@@ -177,4 +781,220 @@ pub fn does_divide(p: &BigInt,q:&BigInt) -> bool {
     };
 
     false
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_small_numbers() {
+        let primes: Vec<u32> = (0..300).filter(|&n| is_prime(&BigInt::from(n), 20)).collect();
+        let expected: Vec<u32> = (0..300).filter(|&n| n >= 2 && (2..n).all(|d| n % d != 0)).collect();
+
+        assert_eq!(primes, expected);
+    }
+
+    #[test]
+    fn test_pseudoprimes() {
+        //carmichael numbers fool the fermat test for every coprime base
+        for n in [561u64, 41041, 825265, 321197185, 5394826801] {
+            assert!(!is_prime(&BigInt::from(n), 20), "{} is composite", n);
+        }
+
+        //strong pseudoprimes to base 2
+        for n in [2047u64, 3277, 4033, 4681, 8321] {
+            assert!(!is_prime(&BigInt::from(n), 20), "{} is composite", n);
+        }
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        let one = BigInt::from(1);
+
+        //mersenne primes
+        assert!(is_prime(&((&one << 127) - &one), 40));
+        assert!(is_prime(&((&one << 521) - &one), 40));
+
+        //2^128 + 1 = 59649589127497217 * 5704689200685129054721
+        assert!(!is_prime(&((&one << 128) + &one), 40));
+
+        //product of two mersenne primes
+        let composite = ((&one << 89) - &one) * ((&one << 107) - &one);
+        assert!(!is_prime(&composite, 40));
+
+        assert!(!is_prime(&BigInt::from(-7), 40));
+    }
+
+    #[test]
+    fn test_montgomery_modular_exponentiation() {
+        let mut rng = rand::rng();
+
+        //one modulus for each limb width, plus the 256/257 boundary. the test profile
+        //doesn't optimise this crate, so the wide ones get fewer rounds
+        for bits in [8u64, 64, 127, 256, 257, 512, 1024, 2048, 3000, 4096, 4097] {
+            let mut n = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits, &mut rng));
+            n.set_bit(0, true);
+            n.set_bit(bits - 1, true);
+
+            let context = MontgomeryContext::new(&n).unwrap();
+            let rounds = if bits > 1024 { 2 } else { 20 };
+
+            for _ in 0..rounds {
+                let a = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits + 8, &mut rng));
+                let e = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(bits, &mut rng));
+
+                let expected = costly_modular_exponentiation(a.clone(), e.clone(), n.clone());
+                assert_eq!(context.modpow(&a, &e), expected);
+                assert_eq!(montgomery_modular_exponentiation(a, e, n.clone()), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_edge_cases() {
+        let n = BigInt::from(17);
+        let context = MontgomeryContext::new(&n).unwrap();
+
+        assert_eq!(context.modpow(&BigInt::from(3), &BigInt::ZERO), BigInt::from(1));
+        assert_eq!(context.modpow(&BigInt::ZERO, &BigInt::from(5)), BigInt::ZERO);
+        assert_eq!(context.modpow(&BigInt::from(-2), &BigInt::from(3)), BigInt::from(9));
+
+        //round trip through montgomery form
+        for a in 0..17 {
+            let a = BigInt::from(a);
+            assert_eq!(context.from_montgomery(&context.to_montgomery(&a)), a);
+        }
+
+        //a bound below the exponent only costs time
+        let e = BigInt::from(1000);
+        for bits in [0, 1, 10, 64, 65, 300] {
+            assert_eq!(context.modpow_bounded(&BigInt::from(3), &e, bits), BigInt::from(3).modpow(&e, &n));
+        }
+
+        //exponents wider than the modulus take more windows
+        let e = (BigInt::from(1) << 700) + BigInt::from(12345);
+        assert_eq!(context.modpow(&BigInt::from(3), &e), costly_modular_exponentiation(BigInt::from(3), e.clone(), n.clone()));
+
+        //even, too small or too wide moduli have no context
+        for bad in [BigInt::from(100), BigInt::from(1), BigInt::ZERO, BigInt::from(-7), (BigInt::from(1) << MONTGOMERY_MAX_BITS) + 1] {
+            assert!(MontgomeryContext::new(&bad).is_none());
+        }
+
+        //and fall back to the costly path
+        let result = montgomery_modular_exponentiation(BigInt::from(3), BigInt::from(5), BigInt::from(100));
+        assert_eq!(result, BigInt::from(43));
+        for a in [0, 1, 3] {
+            let expected = costly_modular_exponentiation(BigInt::from(a), BigInt::from(5), BigInt::from(1));
+            assert_eq!(expected, BigInt::ZERO);
+            assert_eq!(montgomery_modular_exponentiation(BigInt::from(a), BigInt::from(5), BigInt::from(1)), expected);
+        }
+    }
+
+    #[test]
+    fn test_legendre_and_jacobi_symbols() {
+        for p in [3i64, 5, 7, 11, 13, 17, 19, 23] {
+            let squares: Vec<i64> = (1..p).map(|x| x*x % p).collect();
+            for a in -p..2*p {
+                let expected = if a % p == 0 { 0 } else if squares.contains(&a.rem_euclid(p)) { 1 } else { -1 };
+                assert_eq!(legendre_symbol(&BigInt::from(a), &BigInt::from(p)), expected, "({}/{})", a, p);
+                assert_eq!(jacobi_symbol(&BigInt::from(a), &BigInt::from(p)), expected);
+            }
+        }
+
+        //for composite n the jacobi symbol is the product over the prime factors
+        for (n, p, q) in [(15i64, 3i64, 5i64), (21, 3, 7), (45, 5, 9), (221, 13, 17)] {
+            for a in 0..n {
+                let expected = jacobi_symbol(&BigInt::from(a), &BigInt::from(p)) * jacobi_symbol(&BigInt::from(a), &BigInt::from(q));
+                assert_eq!(jacobi_symbol(&BigInt::from(a), &BigInt::from(n)), expected, "({}/{})", a, n);
+            }
+        }
+
+        assert_eq!(jacobi_symbol(&BigInt::from(1001), &BigInt::from(9907)), -1);
+        assert_eq!(jacobi_symbol(&BigInt::from(5), &BigInt::from(1)), 1);
+        //2 is a non-residue mod 3 and mod 5, but (2/15) = 1
+        assert_eq!(jacobi_symbol(&BigInt::from(2), &BigInt::from(15)), 1);
+    }
+
+    #[test]
+    fn test_sqrt_mod_prime() {
+        //3 mod 4 primes use the shortcut, the rest Tonelli-Shanks with s up to 8
+        for p in [2i64, 3, 5, 7, 13, 17, 41, 97, 257] {
+            let p = BigInt::from(p);
+            for a in 0..300 {
+                let a = BigInt::from(a);
+                match sqrt_mod_prime(&a, &p) {
+                    Some(x) => assert_eq!((&x * &x).rem(&p), (&a).rem(&p)),
+                    None => assert_eq!(legendre_symbol(&a, &p), -1)
+                }
+            }
+        }
+
+        //P-224 prime 2^224 - 2^96 + 1, where p - 1 is divisible by 2^96
+        let p = (BigInt::from(1) << 224) - (BigInt::from(1) << 96) + BigInt::from(1);
+        let mut rng = rand::rng();
+        for _ in 0..10 {
+            let x = BigInt::from_biguint(Sign::Plus, rng::generate_random_bits(223, &mut rng));
+            let a = (&x * &x).rem(&p);
+            let root = sqrt_mod_prime(&a, &p).unwrap();
+            assert!(root == x || root == &p - &x);
+        }
+
+        //p = 1 (mod 4), so -1 has a square root
+        let minus_one = &p - BigInt::from(1);
+        let i = sqrt_mod_prime(&minus_one, &p).unwrap();
+        assert_eq!((&i * &i).rem(&p), minus_one);
+    }
+
+    #[test]
+    fn test_generate_prime() {
+        let mut rng = rand::rng();
+
+        for bits in [2u64, 3, 16, 64, 256, 512] {
+            let p = generate_prime(bits, &mut rng);
+
+            assert_eq!(p.bits(), bits);
+            assert!(is_prime(&p, 40));
+        }
+
+        //p, q of exactly 512 bits give an exactly 1024 bit product
+        let p = generate_prime(512, &mut rng);
+        let q = generate_prime(512, &mut rng);
+        assert_eq!((p * q).bits(), 1024);
+    }
+
+    #[test]
+    fn test_generate_prime_congruent() {
+        let mut rng = rand::rng();
+
+        //DSA style p = 1 (mod 2q)
+        let q = generate_prime(160, &mut rng);
+        let two_q = BigInt::from(2) * &q;
+        let p = generate_prime_congruent(512, &BigInt::from(1), &two_q, &mut rng);
+
+        assert_eq!(p.bits(), 512);
+        assert!(is_prime(&p, 40));
+        assert!(does_divide(&(&p - BigInt::from(1)), &q));
+
+        //odd modulus: p = 2 (mod 3)
+        let p = generate_prime_congruent(128, &BigInt::from(2), &BigInt::from(3), &mut rng);
+        assert_eq!(p.bits(), 128);
+        assert_eq!((&p).rem(&BigInt::from(3)), BigInt::from(2));
+        assert!(is_prime(&p, 40));
+    }
+
+    #[test]
+    fn test_generate_safe_prime() {
+        let mut rng = rand::rng();
+
+        for bits in [6u64, 32, 128] {
+            let p = generate_safe_prime(bits, &mut rng);
+            let q = (&p - BigInt::from(1)) / BigInt::from(2);
+
+            assert_eq!(p.bits(), bits);
+            assert!(is_prime(&p, 40));
+            assert!(is_prime(&q, 40));
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use hmac::{Mac, SimpleHmac};
+use hmac::digest::Digest;
+use hmac::digest::core_api::BlockSizeUser;
+use num_bigint::{BigInt, Sign};
+use std::marker::PhantomData;
+
+
+///HMAC-DRBG nonce generator from RFC 6979 section 3.2
+///
+///derives the per-message secret k for DSA and ECDSA from the private key x and the
+///message digest h1, so signing needs no randomness and the same (x, h1) always gives
+///the same k. optional additional data k' is mixed into the seed as in section 3.6,
+///which gives the hedged variant when k' is fresh randomness
+///
+/// fields:
+///     - q = order of the subgroup the nonce lives in
+///     - key, v = HMAC-DRBG state K and V
+pub struct NonceGenerator<D: Digest + BlockSizeUser> {
+    q: BigInt,
+    key: Vec<u8>,
+    v: Vec<u8>,
+    digest: PhantomData<D>
+}
+
+impl<D: Digest + BlockSizeUser> NonceGenerator<D> {
+    ///new() seeds the DRBG (steps a. to g.)
+    ///
+    /// input:
+    ///     - q = subgroup order, q > 1
+    ///     - x = private key, 1 <= x <= q - 1
+    ///     - h1 = message digest H(m) computed with the same hash D
+    ///     - additional_data = k', empty for plain deterministic nonces
+    pub fn new(q: &BigInt, x: &BigInt, h1: &[u8], additional_data: &[u8]) -> Self {
+        assert!(q > &BigInt::from(1), "subgroup order must be greater than 1");
+
+        let hlen = <D as Digest>::output_size();
+        let mut generator = Self {
+            q: q.clone(),
+            key: vec![0x00; hlen],
+            v: vec![0x01; hlen],
+            digest: PhantomData
+        };
+
+        let x_octets = int2octets(x, q);
+        let h1_octets = bits2octets(h1, q);
+
+        //K = HMAC_K(V || 0x00 || int2octets(x) || bits2octets(h1) || k'), V = HMAC_K(V)
+        //K = HMAC_K(V || 0x01 || int2octets(x) || bits2octets(h1) || k'), V = HMAC_K(V)
+        for separator in [0x00, 0x01] {
+            generator.key = generator.hmac(&[&generator.v, &[separator], &x_octets, &h1_octets, additional_data]);
+            generator.v = generator.hmac(&[&generator.v]);
+        }
+
+        generator
+    }
+
+    ///next_nonce() returns the next candidate k in [1, q-1] (step h.)
+    ///
+    ///callers that reject k (e.g. because r or s came out as zero) call it again to
+    ///continue the DRBG as the RFC specifies
+    pub fn next_nonce(&mut self) -> BigInt {
+        let qlen = self.q.bits();
+
+        loop {
+            //T = V_1 || V_2 || ... until T has at least qlen bits
+            let mut t: Vec<u8> = Vec::new();
+            while (8 * t.len() as u64) < qlen {
+                self.v = self.hmac(&[&self.v]);
+                t.extend_from_slice(&self.v);
+            }
+
+            let k = bits2int(&t, qlen);
+
+            //reseed before returning so a rejected k moves the DRBG on:
+            //K = HMAC_K(V || 0x00), V = HMAC_K(V)
+            self.key = self.hmac(&[&self.v, &[0x00]]);
+            self.v = self.hmac(&[&self.v]);
+
+            if k >= BigInt::from(1) && k < self.q {
+                return k
+            }
+        }
+    }
+
+    fn hmac(&self, parts: &[&[u8]]) -> Vec<u8> {
+        let mut mac = <SimpleHmac<D> as Mac>::new_from_slice(&self.key)
+            .expect("HMAC accepts keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+///bits2int() reads a bit string as a big-endian integer and keeps its leftmost qlen
+///bits, which is also how DSA and ECDSA truncate a digest longer than the group order
+pub fn bits2int(bytes: &[u8], qlen: u64) -> BigInt {
+    let value = BigInt::from_bytes_be(Sign::Plus, bytes);
+    let blen = 8 * bytes.len() as u64;
+
+    if blen > qlen {
+        return value >> (blen - qlen)
+    }
+
+    value
+}
+
+//big-endian encoding of x in rlen = ceil(qlen / 8) bytes
+fn int2octets(x: &BigInt, q: &BigInt) -> Vec<u8> {
+    let rlen = q.bits().div_ceil(8) as usize;
+    let (_, bytes) = x.to_bytes_be();
+
+    let mut octets = vec![0u8; rlen.saturating_sub(bytes.len())];
+    octets.extend_from_slice(&bytes[bytes.len().saturating_sub(rlen)..]);
+    octets
+}
+
+//bits2int(h1) reduced mod q and encoded with int2octets
+fn bits2octets(h1: &[u8], q: &BigInt) -> Vec<u8> {
+    let z1 = bits2int(h1, q.bits());
+    let z2 = if &z1 >= q { z1 - q } else { z1 };
+
+    int2octets(&z2, q)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::{Sha256, Sha384, Sha512};
+
+    fn from_hex(hex: &str) -> BigInt {
+        BigInt::parse_bytes(hex.as_bytes(), 16).unwrap()
+    }
+
+    #[test]
+    fn test_detailed_example() {
+        //RFC 6979 A.1: 163-bit subgroup order, so h1 is truncated and reduced mod q
+        let q = from_hex("4000000000000000000020108a2e0cc0d99f8a5ef");
+        let x = from_hex("09a4d6792295a7f730fc3f2b49cbc0f62e862272f");
+        let h1 = Sha256::digest(b"sample");
+
+        assert_eq!(bits2octets(&h1, &q), hex::decode("01795edf0d54db760f156d0dac04c0322b3a204224").unwrap());
+
+        let mut generator = NonceGenerator::<Sha256>::new(&q, &x, &h1, &[]);
+        assert_eq!(generator.next_nonce(), from_hex("23af4074c90a02b3fe61d286d5c87f425e6bdd81b"));
+    }
+
+    #[test]
+    fn test_p256_nonces() {
+        //RFC 6979 A.2.5 nonces for P-256 with SHA-256, SHA-384 and SHA-512
+        let q = from_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+        let x = from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721");
+
+        let nonce_sha256 = |m: &[u8]| NonceGenerator::<Sha256>::new(&q, &x, &Sha256::digest(m), &[]).next_nonce();
+        let nonce_sha384 = |m: &[u8]| NonceGenerator::<Sha384>::new(&q, &x, &Sha384::digest(m), &[]).next_nonce();
+        let nonce_sha512 = |m: &[u8]| NonceGenerator::<Sha512>::new(&q, &x, &Sha512::digest(m), &[]).next_nonce();
+
+        assert_eq!(nonce_sha256(b"sample"), from_hex("a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60"));
+        assert_eq!(nonce_sha384(b"sample"), from_hex("09f634b188cefd98e7ec88b1aa9852d734d0bc272f7d2a47decc6ebeb375aad4"));
+        assert_eq!(nonce_sha512(b"sample"), from_hex("5fa81c63109badb88c1f367b47da606da28cad69aa22c4fe6ad7df73a7173aa5"));
+        assert_eq!(nonce_sha256(b"test"), from_hex("d16b6ae827f17175e040871a1c7ec3500192c4c92677336ec2537acaee0008e0"));
+        assert_eq!(nonce_sha384(b"test"), from_hex("16aeffa357260b04b1dd199693960740066c1a8f3e8edd79070aa914d361b3b8"));
+        assert_eq!(nonce_sha512(b"test"), from_hex("6915d11632aca3c40d5d51c08daf9c555933819548784480e93499000d9f0b7f"));
+    }
+
+    #[test]
+    fn test_additional_data_and_retry() {
+        let q = from_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+        let x = from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721");
+        let h1 = Sha256::digest(b"sample");
+
+        let mut plain = NonceGenerator::<Sha256>::new(&q, &x, &h1, &[]);
+        let mut hedged = NonceGenerator::<Sha256>::new(&q, &x, &h1, &[0x42; 32]);
+        let first = plain.next_nonce();
+
+        assert_ne!(hedged.next_nonce(), first);
+
+        //a rejected nonce is followed by a fresh one
+        let second = plain.next_nonce();
+        assert_ne!(second, first);
+        assert!(second >= BigInt::from(1) && second < q);
+    }
+}
@@ -1,8 +1,40 @@
-use rand;
-use num_bigint::{ BigUint, RandBigInt};
-
-pub fn generate_random_less_than(bound: &BigUint) -> BigUint {
-        let mut rng = rand::thread_rng();
-
-        rng.gen_biguint_below(bound)
-    }
\ No newline at end of file
+use rand::RngCore;
+use num_bigint::BigUint;
+
+pub fn generate_random_less_than(bound: &BigUint) -> BigUint {
+    let mut rng = rand::rng();
+
+    generate_random_below(bound, &mut rng)
+}
+
+///returns a uniformly random integer x with 0 <= x < bound
+///
+///candidates of the same bit length as bound are drawn until one falls below it,
+///so on average fewer than two draws are needed
+pub fn generate_random_below<R: RngCore + ?Sized>(bound: &BigUint, rng: &mut R) -> BigUint {
+    assert!(*bound > BigUint::ZERO, "bound must be positive");
+
+    let bits = bound.bits();
+
+    loop {
+        let candidate = generate_random_bits(bits, rng);
+        if &candidate < bound {
+            return candidate
+        }
+    }
+}
+
+///returns a uniformly random integer of at most `bits` bits
+pub fn generate_random_bits<R: RngCore + ?Sized>(bits: u64, rng: &mut R) -> BigUint {
+    let n_bytes = bits.div_ceil(8) as usize;
+    let mut bytes = vec![0u8; n_bytes];
+    rng.fill_bytes(&mut bytes);
+
+    //clear the excess high bits of the leading byte
+    let excess = (n_bytes as u64) * 8 - bits;
+    if excess > 0 {
+        bytes[0] &= 0xff >> excess;
+    }
+
+    BigUint::from_bytes_be(&bytes)
+}
@@ -1,95 +1,1225 @@
-use std::ops::{Add, Mul, Sub};
-use num_bigint::BigInt;
+use std::fmt;
+use std::ops::{Mul, Rem, Sub};
+use num_bigint::{BigInt, Sign};
+use rand::CryptoRng;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 use crate::number_theory_primitives as helper;
 
+///public exponent used by RsaPrivateKey::generate
+pub const DEFAULT_PUBLIC_EXPONENT: u32 = 65537;
 
-fn main() {
+///smallest modulus accepted anywhere in this module
+pub const MIN_MODULUS_BITS: u64 = 2048;
 
-    //initialise primes p and q
-    let p: BigInt = BigInt::from(23);
-    let q: BigInt = BigInt::from(59);
+//public exponents must satisfy 2^16 < e < 2^256 (FIPS 186-4 B.3.1)
+const MIN_PUBLIC_EXPONENT_BITS: u64 = 17;
+const MAX_PUBLIC_EXPONENT_BITS: u64 = 256;
 
-    //define plaintext
-    let plaintext: BigInt = BigInt::from(999);
+//miller-rabin rounds used when checking the primes of a supplied key
+const PRIMALITY_ROUNDS: usize = 40;
 
-    //if plaintext > p*q, then plaintext changes as we reduce mod n
-    assert!(plaintext < (&p).mul(&q), "plaintext must be strictly less than product of primes");
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RsaError {
+    ModulusTooSmall,
+    InvalidPublicExponent,
+    InvalidPrime,
+    EqualPrimes,
+    PrimesTooClose,
+    InvalidPrivateExponent,
+    InconsistentKey,
+    MessageOutOfRange,
+    FaultDetected,
+    MessageTooLong,
+    Decryption,
+    InvalidSaltLength,
+    InvalidDigestLength,
+    Verification,
+}
 
-    println!("plaintext = {:?}", plaintext);
+impl fmt::Display for RsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            RsaError::ModulusTooSmall => "modulus is smaller than the minimum key size",
+            RsaError::InvalidPublicExponent => "public exponent must be odd and satisfy 2^16 < e < 2^256",
+            RsaError::InvalidPrime => "p or q is not prime",
+            RsaError::EqualPrimes => "p and q must be distinct",
+            RsaError::PrimesTooClose => "p and q are close enough to factor n by Fermat's method",
+            RsaError::InvalidPrivateExponent => "private exponent is too small or not the inverse of e",
+            RsaError::InconsistentKey => "key components do not match",
+            RsaError::MessageOutOfRange => "message representative out of range",
+            RsaError::FaultDetected => "private key operation failed its consistency check",
+            RsaError::MessageTooLong => "message too long",
+            RsaError::Decryption => "decryption error",
+            RsaError::InvalidSaltLength => "salt is too long for the modulus and hash",
+            RsaError::InvalidDigestLength => "digest length does not match the hash function",
+            RsaError::Verification => "invalid signature",
+        };
 
-    let prime_pair: RsaPair = RsaPair { pair : (p, q) };
+        write!(f, "{}", description)
+    }
+}
 
-    //generate public and private keys
-    let keys: KeyPair = prime_pair.key_generation();
+impl std::error::Error for RsaError {}
 
-    println!("public and private keys = {:?}", keys);
+///RSA public key (n, e)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    n: BigInt,
+    e: BigInt
+}
 
-    //encrypt plaintext
-    let ciphertext: BigInt = KeyPair::encrypt(plaintext, keys.k_pub);
+impl RsaPublicKey {
+    ///new() checks the modulus is at least MIN_MODULUS_BITS long and that e is an
+    ///odd exponent in (2^16, 2^256)
+    pub fn new(n: BigInt, e: BigInt) -> Result<Self, RsaError> {
+        if n.bits() < MIN_MODULUS_BITS {
+            return Err(RsaError::ModulusTooSmall)
+        };
 
-    println!("ciphertext = {:?}", ciphertext);
+        validate_public_exponent(&e)?;
 
-    //decrypt ciphertext
-    let plaintext2: BigInt = KeyPair::decrypt(ciphertext, keys.k_priv);
-    
-    println!("back to plaintext = {:?}", plaintext2);
+        if e >= n {
+            return Err(RsaError::InvalidPublicExponent)
+        };
 
-}
+        Ok(Self { n, e })
+    }
 
+    pub fn n(&self) -> &BigInt {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigInt {
+        &self.e
+    }
+
+    ///length of the modulus in bytes
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+
+    ///RSAEP: computes m^e mod n for a message representative 0 <= m < n
+    pub fn encrypt(&self, plaintext: &BigInt) -> Result<BigInt, RsaError> {
+        if plaintext < &BigInt::ZERO || plaintext >= &self.n {
+            return Err(RsaError::MessageOutOfRange)
+        };
+
+        let ciphertext: BigInt = helper::costly_modular_exponentiation(plaintext.clone(), self.e.clone(), self.n.clone());
+
+        Ok(ciphertext)
+    }
+}
 
-#[derive(Debug)]
-struct RsaPair {
-    // p, q = large primes
-    pair: (BigInt, BigInt),
+///RSA private key
+///
+/// fields:
+///     - public = public key (n, e)
+///     - d = e^-1 mod lcm(p-1, q-1)
+///     - p, q = prime factors of n
+///     - dp = d mod (p-1), dq = d mod (q-1) and q_inv = q^-1 mod p, so that private
+///       key operations can run mod p and mod q and be recombined with the CRT
+#[derive(Clone, PartialEq, Eq)]
+pub struct RsaPrivateKey {
+    public: RsaPublicKey,
+    d: BigInt,
+    p: BigInt,
+    q: BigInt,
+    dp: BigInt,
+    dq: BigInt,
+    q_inv: BigInt
 }
 
-#[derive(Debug)]
-struct  KeyPair {
-    k_pub: (BigInt, BigInt),
-    k_priv: (BigInt, BigInt)
+//only the public half is printed so keys can't leak through logs
+impl fmt::Debug for RsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaPrivateKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
 }
 
-impl RsaPair {
-    fn key_generation(self) -> KeyPair {
+impl RsaPrivateKey {
+    ///generates a `bits` bit key with e = 65537
+    pub fn generate<R: CryptoRng + ?Sized>(bits: u64, rng: &mut R) -> Result<Self, RsaError> {
+        Self::generate_with_exponent(bits, &BigInt::from(DEFAULT_PUBLIC_EXPONENT), rng)
+    }
+
+    ///generates a `bits` bit key for the public exponent e
+    ///
+    ///p and q are drawn independently with gcd(p-1, e) = gcd(q-1, e) = 1 and redrawn
+    ///whenever the resulting key fails validation (|p-q| or d too small)
+    pub fn generate_with_exponent<R: CryptoRng + ?Sized>(bits: u64, e: &BigInt, rng: &mut R) -> Result<Self, RsaError> {
+        if bits < MIN_MODULUS_BITS {
+            return Err(RsaError::ModulusTooSmall)
+        };
+
+        validate_public_exponent(e)?;
+
+        let p_bits: u64 = bits / 2;
+        let q_bits: u64 = bits - p_bits;
+
+        loop {
+            let p: BigInt = generate_prime_for_exponent(p_bits, e, rng);
+            let q: BigInt = generate_prime_for_exponent(q_bits, e, rng);
+
+            match Self::from_primes(p, q, e.clone()) {
+                Ok(key) => return Ok(key),
+                Err(RsaError::EqualPrimes | RsaError::PrimesTooClose | RsaError::InvalidPrivateExponent) => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    ///builds a key from its prime factors, computing d = e^-1 mod lcm(p-1, q-1)
+    pub fn from_primes(p: BigInt, q: BigInt, e: BigInt) -> Result<Self, RsaError> {
         let one: BigInt = BigInt::from(1);
 
-        let (p,q) = self.pair;
+        let lambda_n: BigInt = carmichael_lambda(&p, &q);
+        if !helper::is_coprime(e.clone(), lambda_n.clone()) {
+            return Err(RsaError::InvalidPublicExponent)
+        };
+
+        let d: BigInt = helper::multiplicative_inverse(e.clone(), lambda_n.clone()).rem(&lambda_n);
+        debug_assert_eq!((&d).mul(&e).rem(&lambda_n), one);
+
+        Self::from_components(p, q, e, d)
+    }
+
+    ///builds a key from all of its components and validates it
+    pub fn from_components(p: BigInt, q: BigInt, e: BigInt, d: BigInt) -> Result<Self, RsaError> {
+        let one: BigInt = BigInt::from(1);
+
+        //the CRT exponents below are reduced mod p-1 and q-1
+        if p <= one || q <= one {
+            return Err(RsaError::InvalidPrime)
+        };
+
         let n: BigInt = (&p).mul(&q);
+        let public: RsaPublicKey = RsaPublicKey::new(n, e)?;
+
+        if p == q {
+            return Err(RsaError::EqualPrimes)
+        };
+
+        let dp: BigInt = (&d).rem(&p - &one);
+        let dq: BigInt = (&d).rem(&q - &one);
+        let q_inv: BigInt = match q.modinv(&p) {
+            Some(q_inv) => q_inv,
+            None => return Err(RsaError::InvalidPrime),
+        };
+
+        let key = Self { public, d, p, q, dp, dq, q_inv };
+
+        key.validate()?;
+
+        Ok(key)
+    }
+
+    ///checks the private key is consistent and not weak:
+    /// 1. p and q are distinct primes of half the modulus size with n = p*q
+    /// 2. |p - q| > 2^(nlen/2 - 100), so n can't be factored by Fermat's method
+    /// 3. d*e = 1 mod lcm(p-1, q-1) and 2^(nlen/2) < d < n, ruling out Wiener style attacks
+    ///
+    ///d may have been reduced mod (p-1)(q-1) rather than lcm(p-1, q-1) by other tools, so
+    ///it is only bounded by n
+    pub fn validate(&self) -> Result<(), RsaError> {
+        let one: BigInt = BigInt::from(1);
+        let (p, q, d) = (&self.p, &self.q, &self.d);
+        let n: &BigInt = &self.public.n;
+        let e: &BigInt = &self.public.e;
+        let half_bits: u64 = n.bits() / 2;
+
+        if p == q {
+            return Err(RsaError::EqualPrimes)
+        };
+
+        if &(p.mul(q)) != n {
+            return Err(RsaError::InconsistentKey)
+        };
+
+        if p.bits() < half_bits || q.bits() < half_bits {
+            return Err(RsaError::InconsistentKey)
+        };
+
+        if !helper::is_prime(p, PRIMALITY_ROUNDS) || !helper::is_prime(q, PRIMALITY_ROUNDS) {
+            return Err(RsaError::InvalidPrime)
+        };
+
+        let difference: BigInt = if p > q { p.sub(q) } else { q.sub(p) };
+        if difference.bits() <= half_bits.saturating_sub(100) {
+            return Err(RsaError::PrimesTooClose)
+        };
+
+        let lambda_n: BigInt = carmichael_lambda(p, q);
+        if d <= &(&one << half_bits) || d >= n || d.mul(e).rem(&lambda_n) != one {
+            return Err(RsaError::InvalidPrivateExponent)
+        };
+
+        Ok(())
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public
+    }
+
+    pub fn d(&self) -> &BigInt {
+        &self.d
+    }
+
+    pub fn primes(&self) -> (&BigInt, &BigInt) {
+        (&self.p, &self.q)
+    }
+
+    ///RSADP: computes c^d mod n for a ciphertext representative 0 <= c < n
+    pub fn decrypt(&self, ciphertext: &BigInt) -> Result<BigInt, RsaError> {
+        self.private_operation(ciphertext)
+    }
+
+    //computes x^d mod n with the chinese remainder theorem (RFC 8017 5.1.2 2.b)
+    //
+    // 1. m_1 = x^dp mod p and m_2 = x^dq mod q, two half size exponentiations
+    // 2. h = q_inv * (m_1 - m_2) mod p
+    // 3. m = m_2 + q*h
+    //
+    //a fault in either half (glitch, bit flip) would let anyone who sees the result
+    //factor n with one gcd, so m^e = x mod n is re-checked before m is released
+    fn private_operation(&self, x: &BigInt) -> Result<BigInt, RsaError> {
+        let n: &BigInt = &self.public.n;
+
+        if x < &BigInt::ZERO || x >= n {
+            return Err(RsaError::MessageOutOfRange)
+        };
+
+        //p, q, dp and dq are secret, so both halves go through the constant time
+        //montgomery exponentiation
+        let m_1: BigInt = helper::montgomery_modular_exponentiation(x.clone(), self.dp.clone(), self.p.clone());
+        let m_2: BigInt = helper::montgomery_modular_exponentiation(x.clone(), self.dq.clone(), self.q.clone());
+
+        let mut h: BigInt = (&self.q_inv * (m_1 - &m_2)).rem(&self.p);
+        if h < BigInt::ZERO {
+            h += &self.p;
+        }
 
-        let phi_p: BigInt = p.sub(&one);
-        let phi_q: BigInt = q.sub(&one);
-        let phi_n: BigInt = BigInt::mul(phi_p, phi_q);
-        
-        //initialize
-        let mut encryption_exponent:BigInt = BigInt::from(2);
+        let m: BigInt = m_2 + &self.q * h;
 
-        while !helper::is_coprime(encryption_exponent.clone(), phi_n.clone()) {
-            encryption_exponent = encryption_exponent.add(&one);
+        if &self.public.encrypt(&m)? != x {
+            return Err(RsaError::FaultDetected)
         };
 
-        let decryption_exponent: BigInt = helper::multiplicative_inverse(encryption_exponent.clone(), phi_n);
-        
-        KeyPair { k_pub: (n.clone(), encryption_exponent), k_priv: (n.clone(), decryption_exponent) }
+        Ok(m)
+    }
+}
+
+///RSAES-OAEP encryption (RFC 8017 7.1.1) with D as both the label hash and the MGF1
+///hash, SHA-256 being the usual choice
+///
+///input:
+///     - pub_key = recipient's public key with k byte modulus
+///     - label = optional label associated with the message, may be empty
+///     - msg = message of at most k - 2*hLen - 2 bytes, k - 66 for SHA-256
+///     - rng = source of the random seed
+///output:
+///     - k byte ciphertext
+pub fn encrypt_oaep<D: Digest, R: CryptoRng + ?Sized>(pub_key: &RsaPublicKey, label: &[u8], msg: &[u8], rng: &mut R) -> Result<Vec<u8>, RsaError> {
+    let k: usize = pub_key.size();
+    let h_len: usize = <D as Digest>::output_size();
+
+    if msg.len() + 2 * h_len + 2 > k {
+        return Err(RsaError::MessageTooLong)
+    };
+
+    //DB = lHash || PS || 0x01 || M
+    let mut db: Vec<u8> = vec![0u8; k - h_len - 1];
+    db[..h_len].copy_from_slice(&D::digest(label));
+    db[k - h_len - 2 - msg.len()] = 0x01;
+    db[k - h_len - 1 - msg.len()..].copy_from_slice(msg);
+
+    let mut seed: Vec<u8> = vec![0u8; h_len];
+    rng.fill_bytes(&mut seed);
+
+    xor_in_place(&mut db, &mgf1::<D>(&seed, k - h_len - 1));
+    xor_in_place(&mut seed, &mgf1::<D>(&db, h_len));
+
+    //EM = 0x00 || maskedSeed || maskedDB
+    let mut em: Vec<u8> = Vec::with_capacity(k);
+    em.push(0x00);
+    em.extend_from_slice(&seed);
+    em.extend_from_slice(&db);
+
+    let ciphertext: BigInt = pub_key.encrypt(&os2ip(&em))?;
+
+    i2osp(&ciphertext, k)
+}
+
+///RSAES-OAEP decryption (RFC 8017 7.1.2) with D as both the label hash and the MGF1
+///hash, which must match the ones used to encrypt
+///
+///every failure, whether in the ciphertext length, the RSA operation or any part of
+///the padding, is reported as the same RsaError::Decryption. the padding is checked
+///without data dependent branches, so callers learn nothing about which check failed
+///(Manger's attack)
+pub fn decrypt_oaep<D: Digest>(priv_key: &RsaPrivateKey, label: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, RsaError> {
+    let k: usize = priv_key.public_key().size();
+    let h_len: usize = <D as Digest>::output_size();
+
+    if ciphertext.len() != k || k < 2 * h_len + 2 {
+        return Err(RsaError::Decryption)
+    };
+
+    let em: Vec<u8> = match priv_key.decrypt(&os2ip(ciphertext)) {
+        Ok(m) => i2osp(&m, k).map_err(|_| RsaError::Decryption)?,
+        Err(_) => return Err(RsaError::Decryption),
+    };
+
+    let mut seed: Vec<u8> = em[1..h_len + 1].to_vec();
+    let mut db: Vec<u8> = em[h_len + 1..].to_vec();
+    xor_in_place(&mut seed, &mgf1::<D>(&db, h_len));
+    xor_in_place(&mut db, &mgf1::<D>(&seed, k - h_len - 1));
+
+    //Y = 0x00 and lHash' = lHash
+    let mut valid: Choice = em[0].ct_eq(&0x00) & db[..h_len].ct_eq(&D::digest(label)[..]);
+
+    //PS is a run of zeros terminated by 0x01, anything else before the 0x01 is invalid
+    let mut looking: Choice = Choice::from(1);
+    let mut separator: u32 = 0;
+    for (i, byte) in db[h_len..].iter().enumerate() {
+        let is_zero: Choice = byte.ct_eq(&0x00);
+        let is_one: Choice = byte.ct_eq(&0x01);
+
+        separator.conditional_assign(&(i as u32), looking & is_one);
+        valid &= !(looking & !is_zero & !is_one);
+        looking &= !is_one;
+    }
+    valid &= !looking;
+
+    if !bool::from(valid) {
+        return Err(RsaError::Decryption)
+    };
+
+    Ok(db[h_len + separator as usize + 1..].to_vec())
+}
+
+///hash functions usable with EMSA-PKCS1-v1_5, which prefixes the digest with the DER
+///encoding of its DigestInfo (RFC 8017 9.2 note 1)
+pub trait Pkcs1v15Digest: Digest {
+    const DIGEST_INFO_PREFIX: &'static [u8];
+}
+
+impl Pkcs1v15Digest for Sha256 {
+    const DIGEST_INFO_PREFIX: &'static [u8] = &[
+        0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00, 0x04, 0x20
+    ];
+}
+
+impl Pkcs1v15Digest for Sha384 {
+    const DIGEST_INFO_PREFIX: &'static [u8] = &[
+        0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02, 0x05, 0x00, 0x04, 0x30
+    ];
+}
+
+impl Pkcs1v15Digest for Sha512 {
+    const DIGEST_INFO_PREFIX: &'static [u8] = &[
+        0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05, 0x00, 0x04, 0x40
+    ];
+}
+
+///RSASSA-PSS signature (RFC 8017 8.1.1) using hash D for both the message and MGF1
+///
+///input:
+///     - priv_key = signer's private key
+///     - msg = message to sign
+///     - salt_len = length of the random salt in bytes, commonly the digest length
+///     - rng = source of the salt
+///output:
+///     - k byte signature
+pub fn sign_pss<D: Digest, R: CryptoRng + ?Sized>(priv_key: &RsaPrivateKey, msg: &[u8], salt_len: usize, rng: &mut R) -> Result<Vec<u8>, RsaError> {
+    sign_pss_prehashed::<D, R>(priv_key, &D::digest(msg), salt_len, rng)
+}
+
+///RSASSA-PSS signature over a D hasher the caller has fed with update()
+pub fn sign_pss_digest<D: Digest, R: CryptoRng + ?Sized>(priv_key: &RsaPrivateKey, digest: D, salt_len: usize, rng: &mut R) -> Result<Vec<u8>, RsaError> {
+    sign_pss_prehashed::<D, R>(priv_key, &digest.finalize(), salt_len, rng)
+}
+
+///RSASSA-PSS signature over a digest the caller has already computed with D
+pub fn sign_pss_prehashed<D: Digest, R: CryptoRng + ?Sized>(priv_key: &RsaPrivateKey, digest: &[u8], salt_len: usize, rng: &mut R) -> Result<Vec<u8>, RsaError> {
+    let mut salt: Vec<u8> = vec![0u8; salt_len];
+    rng.fill_bytes(&mut salt);
+
+    let em_bits: u64 = priv_key.public_key().n().bits() - 1;
+    let em: Vec<u8> = emsa_pss_encode::<D>(digest, &salt, em_bits)?;
+
+    sign_encoded(priv_key, &em)
+}
+
+///RSASSA-PSS verification (RFC 8017 8.1.2) with the salt length used by the signer
+pub fn verify_pss<D: Digest>(pub_key: &RsaPublicKey, msg: &[u8], signature: &[u8], salt_len: usize) -> Result<(), RsaError> {
+    verify_pss_prehashed::<D>(pub_key, &D::digest(msg), signature, salt_len)
+}
+
+///RSASSA-PSS verification of a D hasher the caller has fed with update()
+pub fn verify_pss_digest<D: Digest>(pub_key: &RsaPublicKey, digest: D, signature: &[u8], salt_len: usize) -> Result<(), RsaError> {
+    verify_pss_prehashed::<D>(pub_key, &digest.finalize(), signature, salt_len)
+}
+
+///RSASSA-PSS verification of a digest the caller has already computed with D
+pub fn verify_pss_prehashed<D: Digest>(pub_key: &RsaPublicKey, digest: &[u8], signature: &[u8], salt_len: usize) -> Result<(), RsaError> {
+    let em_bits: u64 = pub_key.n().bits() - 1;
+    let em: Vec<u8> = recover_encoded(pub_key, signature, em_bits.div_ceil(8) as usize)?;
+
+    emsa_pss_verify::<D>(digest, &em, salt_len, em_bits)
+}
+
+///RSASSA-PKCS1-v1_5 signature (RFC 8017 8.2.1), deterministic for a given key and message
+pub fn sign_pkcs1v15<D: Pkcs1v15Digest>(priv_key: &RsaPrivateKey, msg: &[u8]) -> Result<Vec<u8>, RsaError> {
+    sign_pkcs1v15_prehashed::<D>(priv_key, &D::digest(msg))
+}
+
+///RSASSA-PKCS1-v1_5 signature over a D hasher the caller has fed with update()
+pub fn sign_pkcs1v15_digest<D: Pkcs1v15Digest>(priv_key: &RsaPrivateKey, digest: D) -> Result<Vec<u8>, RsaError> {
+    sign_pkcs1v15_prehashed::<D>(priv_key, &digest.finalize())
+}
+
+///RSASSA-PKCS1-v1_5 signature over a digest the caller has already computed with D
+pub fn sign_pkcs1v15_prehashed<D: Pkcs1v15Digest>(priv_key: &RsaPrivateKey, digest: &[u8]) -> Result<Vec<u8>, RsaError> {
+    let em: Vec<u8> = emsa_pkcs1v15_encode::<D>(digest, priv_key.public_key().size())?;
+
+    sign_encoded(priv_key, &em)
+}
+
+///RSASSA-PKCS1-v1_5 verification (RFC 8017 8.2.2)
+pub fn verify_pkcs1v15<D: Pkcs1v15Digest>(pub_key: &RsaPublicKey, msg: &[u8], signature: &[u8]) -> Result<(), RsaError> {
+    verify_pkcs1v15_prehashed::<D>(pub_key, &D::digest(msg), signature)
+}
+
+///RSASSA-PKCS1-v1_5 verification of a D hasher the caller has fed with update()
+pub fn verify_pkcs1v15_digest<D: Pkcs1v15Digest>(pub_key: &RsaPublicKey, digest: D, signature: &[u8]) -> Result<(), RsaError> {
+    verify_pkcs1v15_prehashed::<D>(pub_key, &digest.finalize(), signature)
+}
+
+///RSASSA-PKCS1-v1_5 verification of a digest the caller has already computed with D
+pub fn verify_pkcs1v15_prehashed<D: Pkcs1v15Digest>(pub_key: &RsaPublicKey, digest: &[u8], signature: &[u8]) -> Result<(), RsaError> {
+    let k: usize = pub_key.size();
+    let em: Vec<u8> = recover_encoded(pub_key, signature, k)?;
+    let expected: Vec<u8> = emsa_pkcs1v15_encode::<D>(digest, k)?;
+
+    if !bool::from(em.ct_eq(&expected)) {
+        return Err(RsaError::Verification)
+    };
+
+    Ok(())
+}
+
+//RSASP1 on an encoded message: s = EM^d mod n as a k byte string
+fn sign_encoded(priv_key: &RsaPrivateKey, em: &[u8]) -> Result<Vec<u8>, RsaError> {
+    let signature: BigInt = priv_key.private_operation(&os2ip(em))?;
+
+    i2osp(&signature, priv_key.public_key().size())
+}
+
+//RSAVP1: EM = s^e mod n as an em_len byte string. every failure is a Verification error
+fn recover_encoded(pub_key: &RsaPublicKey, signature: &[u8], em_len: usize) -> Result<Vec<u8>, RsaError> {
+    if signature.len() != pub_key.size() {
+        return Err(RsaError::Verification)
+    };
+
+    let m: BigInt = pub_key.encrypt(&os2ip(signature)).map_err(|_| RsaError::Verification)?;
+
+    i2osp(&m, em_len).map_err(|_| RsaError::Verification)
+}
+
+//EMSA-PSS-ENCODE (RFC 8017 9.1.1)
+//
+// 1. H = Hash(0x00 * 8 || mHash || salt)
+// 2. DB = PS || 0x01 || salt, masked with MGF1(H)
+// 3. EM = maskedDB || H || 0xbc, with the top 8*emLen - emBits bits cleared
+fn emsa_pss_encode<D: Digest>(digest: &[u8], salt: &[u8], em_bits: u64) -> Result<Vec<u8>, RsaError> {
+    let h_len: usize = <D as Digest>::output_size();
+    let em_len: usize = em_bits.div_ceil(8) as usize;
+
+    if digest.len() != h_len {
+        return Err(RsaError::InvalidDigestLength)
+    };
+
+    if em_len < h_len + salt.len() + 2 {
+        return Err(RsaError::InvalidSaltLength)
+    };
+
+    let h = pss_hash::<D>(digest, salt);
+
+    let mut db: Vec<u8> = vec![0u8; em_len - h_len - 1];
+    let salt_start: usize = db.len() - salt.len();
+    db[salt_start - 1] = 0x01;
+    db[salt_start..].copy_from_slice(salt);
+
+    xor_in_place(&mut db, &mgf1::<D>(&h, em_len - h_len - 1));
+    db[0] &= 0xff >> (8 * em_len as u64 - em_bits);
+
+    let mut em: Vec<u8> = db;
+    em.extend_from_slice(&h);
+    em.push(0xbc);
+
+    Ok(em)
+}
+
+//EMSA-PSS-VERIFY (RFC 8017 9.1.2)
+fn emsa_pss_verify<D: Digest>(digest: &[u8], em: &[u8], salt_len: usize, em_bits: u64) -> Result<(), RsaError> {
+    let h_len: usize = <D as Digest>::output_size();
+    let em_len: usize = em.len();
+    let top_bits: u64 = 8 * em_len as u64 - em_bits;
+
+    if digest.len() != h_len {
+        return Err(RsaError::InvalidDigestLength)
+    };
+
+    if em_len < h_len + salt_len + 2 || em[em_len - 1] != 0xbc {
+        return Err(RsaError::Verification)
+    };
+
+    let (masked_db, rest) = em.split_at(em_len - h_len - 1);
+    let h: &[u8] = &rest[..h_len];
+
+    if top_bits > 0 && masked_db[0] >> (8 - top_bits) != 0 {
+        return Err(RsaError::Verification)
+    };
+
+    let mut db: Vec<u8> = masked_db.to_vec();
+    xor_in_place(&mut db, &mgf1::<D>(h, masked_db.len()));
+    db[0] &= 0xff >> top_bits;
+
+    //DB = PS || 0x01 || salt with PS all zero
+    let salt_start: usize = db.len() - salt_len;
+    if db[..salt_start - 1].iter().any(|&byte| byte != 0x00) || db[salt_start - 1] != 0x01 {
+        return Err(RsaError::Verification)
+    };
+
+    if pss_hash::<D>(digest, &db[salt_start..])[..] != *h {
+        return Err(RsaError::Verification)
+    };
+
+    Ok(())
+}
+
+//H = Hash(0x00 * 8 || mHash || salt)
+fn pss_hash<D: Digest>(digest: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    hasher.update([0u8; 8]);
+    hasher.update(digest);
+    hasher.update(salt);
+
+    hasher.finalize().to_vec()
+}
+
+//EMSA-PKCS1-v1_5-ENCODE (RFC 8017 9.2): EM = 0x00 || 0x01 || PS || 0x00 || T, where
+//T is the DigestInfo of the digest and PS is at least 8 bytes of 0xff
+fn emsa_pkcs1v15_encode<D: Pkcs1v15Digest>(digest: &[u8], em_len: usize) -> Result<Vec<u8>, RsaError> {
+    if digest.len() != <D as Digest>::output_size() {
+        return Err(RsaError::InvalidDigestLength)
+    };
+
+    let t_len: usize = D::DIGEST_INFO_PREFIX.len() + digest.len();
+    if em_len < t_len + 11 {
+        return Err(RsaError::MessageTooLong)
+    };
+
+    let mut em: Vec<u8> = vec![0xff; em_len];
+    em[0] = 0x00;
+    em[1] = 0x01;
+    em[em_len - t_len - 1] = 0x00;
+    em[em_len - t_len..em_len - digest.len()].copy_from_slice(D::DIGEST_INFO_PREFIX);
+    em[em_len - digest.len()..].copy_from_slice(digest);
+
+    Ok(em)
+}
 
+//MGF1 (RFC 8017 B.2.1): T = H(seed || 0) || H(seed || 1) || ... truncated to len bytes
+fn mgf1<D: Digest>(seed: &[u8], len: usize) -> Vec<u8> {
+    let mut mask: Vec<u8> = Vec::with_capacity(len + <D as Digest>::output_size());
+    let mut counter: u32 = 0;
+
+    while mask.len() < len {
+        let mut hasher = D::new();
+        hasher.update(seed);
+        hasher.update(counter.to_be_bytes());
+        mask.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+
+    mask.truncate(len);
+    mask
+}
+
+fn xor_in_place(data: &mut [u8], mask: &[u8]) {
+    for (byte, mask_byte) in data.iter_mut().zip(mask) {
+        *byte ^= mask_byte;
     }
+}
+
+//integer to big endian octet string of exactly len bytes (RFC 8017 4.1)
+fn i2osp(x: &BigInt, len: usize) -> Result<Vec<u8>, RsaError> {
+    let (_, bytes) = x.to_bytes_be();
+
+    if x.sign() == Sign::Minus || bytes.len() > len {
+        return Err(RsaError::MessageOutOfRange)
+    };
+
+    let mut padded: Vec<u8> = vec![0u8; len - bytes.len()];
+    padded.extend_from_slice(&bytes);
+
+    Ok(padded)
+}
+
+//big endian octet string to non-negative integer (RFC 8017 4.2)
+fn os2ip(bytes: &[u8]) -> BigInt {
+    BigInt::from_bytes_be(Sign::Plus, bytes)
+}
+
+fn validate_public_exponent(e: &BigInt) -> Result<(), RsaError> {
+    let bits: u64 = e.bits();
+
+    if helper::does_divide(e, &BigInt::from(2)) || !(MIN_PUBLIC_EXPONENT_BITS..=MAX_PUBLIC_EXPONENT_BITS).contains(&bits) {
+        return Err(RsaError::InvalidPublicExponent)
+    };
+
+    Ok(())
+}
+
+//lcm(p-1, q-1)
+fn carmichael_lambda(p: &BigInt, q: &BigInt) -> BigInt {
+    let one: BigInt = BigInt::from(1);
+    let phi_p: BigInt = p.sub(&one);
+    let phi_q: BigInt = q.sub(&one);
+    let gcd: BigInt = helper::gcd(phi_p.clone(), phi_q.clone());
+
+    (phi_p.mul(phi_q)) / gcd
+}
+
+//random prime p of `bits` bits with gcd(p-1, e) = 1, so that e is invertible
+fn generate_prime_for_exponent<R: CryptoRng + ?Sized>(bits: u64, e: &BigInt, rng: &mut R) -> BigInt {
+    loop {
+        let p: BigInt = helper::generate_prime(bits, rng);
 
+        if helper::is_coprime(&p - BigInt::from(1), e.clone()) {
+            return p
+        };
+    }
 }
 
 
-impl KeyPair {
-    fn encrypt(plaintext: BigInt, k_pub: (BigInt, BigInt)) -> BigInt {
-        let (n, encryption_exponent) = k_pub;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha1::Sha1;
+    use sha2::Sha512_256;
+    use sha3::{Sha3_256, Sha3_384};
+
+    //2048 bit key generated with `openssl genpkey -algorithm RSA`
+    const TEST_P: &str = "fd704d120cee43785fad13c8398eef1223d75b29670eb48ed2e48e22d2c5be7b81cfd2a262da2b29c58854dc57632f75f2bcd25988ef1306443546a6f6782c5a6f213a3aeb29fa63794befa045307e20cd1ccc21deef56010e397c6c0c31803b6e70c61effa1217360f56f93e2246c580fed381aed3a1c594c7b98914412fb9f";
+    const TEST_Q: &str = "f6b190c4350d7b27e40def0ac95028344d5e9c75a0025fc095d093eb7de5c15a22027fee55b8b14b0f480f298ed9c16fabc19dcf0a6ca728d3f027317b5ac0380182b4b1b9af4ba40328e961d98f101f3e0f0f5913e1b3ed8cce3c18b446d53ee29a31fc0816e4e992702905471fb3848647030a2448a59c441da1147d8325c7";
+    const TEST_D: &str = "5f5c269853c03a18a8328be97118f5ff32146b8ce5bcbb362b6f9a0f60b3457a12045a77e36b850d1afde4e0bd862b46238078e18148232b96c43dee023fddcbcdaa2dd5dc5230bff6f53e8b26aeea28dff0a9493608a3d3ef306ee3d8d461a492f0bbc703ab09a4f75f64a728a81961f9f83d51792731730e3781be6f608b57fbdc58e9492392242160dfdcb39c794d42e5efd1d1f7805fd6f5f837594ce4922213be777c476d05d9b2d42bccdb8053586d086ccff13ffb7f3800254d6b15f21d53208106df62b7dbc03a37a28adab707de9e0b86ffccfc10e4a6d50109917255709e356559f70e4c5d4000cebfc0e1b12f35744d1ab8490d9680973c49a0df";
+
+    fn from_hex(hex: &str) -> BigInt {
+        BigInt::parse_bytes(hex.as_bytes(), 16).unwrap()
+    }
+
+    fn test_key() -> RsaPrivateKey {
+        RsaPrivateKey::from_components(from_hex(TEST_P), from_hex(TEST_Q), BigInt::from(65537), from_hex(TEST_D)).unwrap()
+    }
+
+    #[test]
+    fn test_generate_2048_bits() {
+        let mut rng = rand::rng();
+        let private_key = RsaPrivateKey::generate(2048, &mut rng).unwrap();
+        let public_key = private_key.public_key();
+
+        assert_eq!(public_key.n().bits(), 2048);
+        assert_eq!(public_key.e(), &BigInt::from(65537));
+        assert_eq!(public_key.size(), 256);
+
+        //encrypt then decrypt a random message representative
+        let plaintext = BigInt::from_biguint(num_bigint::Sign::Plus, crate::rng::generate_random_bits(2047, &mut rng));
+        let ciphertext = public_key.encrypt(&plaintext).unwrap();
+        assert_ne!(ciphertext, plaintext);
+        assert_eq!(private_key.decrypt(&ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_openssl_key() {
+        let private_key = test_key();
+
+        //d computed from the primes matches the one openssl chose
+        let rebuilt = RsaPrivateKey::from_primes(from_hex(TEST_P), from_hex(TEST_Q), BigInt::from(65537)).unwrap();
+        assert_eq!(rebuilt, private_key);
+
+        let plaintext = BigInt::from(999);
+        let ciphertext = private_key.public_key().encrypt(&plaintext).unwrap();
+        assert_eq!(private_key.decrypt(&ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_crt_matches_plain_exponentiation() {
+        let mut rng = rand::rng();
+        let private_key = test_key();
+        let n = private_key.public_key().n();
+
+        for _ in 0..10 {
+            let x = BigInt::from_biguint(num_bigint::Sign::Plus, crate::rng::generate_random_below(n.magnitude(), &mut rng));
+            let expected = helper::costly_modular_exponentiation(x.clone(), private_key.d().clone(), n.clone());
 
-        let ciphertext: BigInt = helper::costly_modular_exponentiation(plaintext, encryption_exponent, n);
+            assert_eq!(private_key.decrypt(&x).unwrap(), expected);
+        }
 
-        ciphertext
+        assert_eq!(private_key.decrypt(&BigInt::ZERO).unwrap(), BigInt::ZERO);
+        assert_eq!(private_key.decrypt(&BigInt::from(1)).unwrap(), BigInt::from(1));
     }
 
-    fn decrypt(ciphertext:BigInt, k_priv: (BigInt, BigInt)) -> BigInt {
-        let (n, decryption_exponent) = k_priv;
+    #[test]
+    fn test_fault_detected() {
+        let private_key = test_key();
+        let plaintext = private_key.public_key().n() >> 1;
+        let ciphertext = private_key.public_key().encrypt(&plaintext).unwrap();
+        assert_eq!(private_key.decrypt(&ciphertext).unwrap(), plaintext);
 
-        let plaintext: BigInt = helper::costly_modular_exponentiation(ciphertext, decryption_exponent, n);
-        
-        plaintext
+        //simulate a fault in the mod p half of the computation
+        let mut faulty_key = private_key.clone();
+        faulty_key.dp += BigInt::from(1);
+        assert_eq!(faulty_key.decrypt(&ciphertext), Err(RsaError::FaultDetected));
+
+        //and in the recombination step
+        let mut faulty_key = private_key.clone();
+        faulty_key.q_inv += BigInt::from(1);
+        assert_eq!(faulty_key.decrypt(&ciphertext), Err(RsaError::FaultDetected));
+    }
+
+    #[test]
+    fn test_oaep_openssl_vectors() {
+        let private_key = test_key();
+
+        //ciphertexts produced by OpenSSL (RSA_PKCS1_OAEP_PADDING, SHA-256 for both the
+        //label hash and MGF1) under the test key
+        let vectors: [(&[u8], Vec<u8>, &str); 3] = [
+            (b"", b"pkc OAEP test vector".to_vec(), "cff7508a2fb5bf9f8b86a2094a32a8256e7de10c20f4bf0513e55191c3c31581b693fdc693e575ba03e0bbef9a68554132e29b26149ef70c3e674b73962a220d31b3ce72856016e83e46d8c22722f85e62fe074868b52c9f31d09565a50e894ca155f8e3f88aac369b67b635521d300d5c64cb85b9e5a028a0e3708f916e9bc8ec5ce0f7e4dc7c0dbb68b411431f4a0a34a860e04bc6044ee01c2285a083fb6839d0a0d200ec22e3995fa8c39dc42eece4b5ff2137d52eb0f752efcd28f43a5c88855c447103b78ded3e4f8094c08adae725784ab1033ac569058aebbfff2d288d1a2a9c2d861289b025ee64e444884a01d912f0db3508d6949445e15967fdde"),
+            (b"pkc label", b"".to_vec(), "3d3445b41a5c285db8cbfb7d7cc0b2161749dbb6306d5dc56b2a9f215b50c0efebf418d1014d5082fdda313ee2a99a2c46c09a815990284cb7fa16763c899557e5b0d5041269b7a2d543c19dc4cde7e02f542ec4e809ece847c55c1bbc206250f7f1844f4b93597c9fbb190b155c6431a7ef2ce9b85c31831ca4f21e230a7fd6126075679ab255bab57823571e3cd2f4f9c07b5ec131149b1f3f9583392be4924b0491304881ea57bdb63087c43be00c333410aa7e061c8b07d705abf68142b8a9e2bd9b7d04e94c4e2fe7ac5a036e35789b3032a1c0ef82d3ed147eabf12379279aec69aeee386e6cd35d375ad28378c6058be5552ce2caec58943d233385d8"),
+            (b"", (0..190).collect(), "009b2ae55eedc84c0d4f23998a6a7e225b2c4407ff95caef1cca68a9b69ff31f65e487f20c5036a625dfe42981eb595a379619ebf7da3ea6d37522be163fa01d55077eb2cfba585fe2e54065e64e1100453c5615d2e2649cdec2f728f186ccea5640603304b0d260c286421bc7dc02ac731f5f56b6a680183d2473686cea0bcb67d72073f1016f4adc375d1859a91172595e5216db74a06bb3b8db6fee3d78a17b438ccbb6a1eb93745a4e15c86ec86a7e13051d7338c3a5fc0e52575aa7120751bca951a4b90a3ddd338d9627ab75d78fd73edb0186b3fa0e5f9ae788ac4ff339f7d3c53e1b4f48a4f3f3b0de015cff3bf86c4c0d44f78983c863e07912cb7e"),
+        ];
+
+        for (label, msg, ciphertext) in vectors {
+            let ciphertext = hex::decode(ciphertext).unwrap();
+            assert_eq!(decrypt_oaep::<Sha256>(&private_key, label, &ciphertext).unwrap(), msg);
+        }
+
+        //the same message with SHA-384 and SHA3-256 (rsa_oaep_md and rsa_mgf1_md)
+        let sha384 = hex::decode("7682814e39be52f8bf0ce052452ced79242d636330818d12e82c7d8e0258251728a147a7884195c27a0f41f98084fc438af441500fe03d8f1701a584b576442f7929b74186997856a3755136a1a2e4f2d8fe067e3676392276822a31c2602f9164d7a7ca6c103d5bd5a6d90200204386b8a6e8cb7d08395ddf404a1fc3242d9d1928e665659b558b64a7db9097a94d598836b2fe0e5f98fe718f88c5c9048d09bf7f6075095c398463b840f8f36dc4b7273f06bb66ed57ebededa09fcb3196bd9e9bb07d2841d4fb498349ed782b196a8a1cc16ad5d5e1af92c0ee0d944043816d598feebb49b611b76a9558771e0f72a99fcd357e9faccd6bd5b86995562fb6").unwrap();
+        let sha3_256 = hex::decode("696a6f1480504bcf6f844a3318c86df1b0f086838224341a66b7159ca3344e388e9f6a7c1ad335cc80be0a73b201fb158fd4c25f5a58b57ec44e8ae4fb60e93b357d391d106324affdbf7607f2ca2282e6a3cc877ea5417b451e854f77b3d57efd89adb813c2142bd07d81522d60c992ea047b42866f58360987a7afd049bb0ea553e5e0d494084c530d689f64603c1ff166eccd3875bd9f9d7f51e1d0dd582ef4d5d6694907e5f2ff36e015a1209a4aacd56985ebb06ba6e44efceb2eacd2788d4948a283013ceabddfd8de94eec7d6cefcc3ef37a0ce538485a86d33b82ae603ba02d3e5b0736118cd67d5a902bfb05718d5d42be93d92dfc716c733a446cf").unwrap();
+        assert_eq!(decrypt_oaep::<Sha384>(&private_key, b"", &sha384).unwrap(), b"pkc OAEP test vector");
+        assert_eq!(decrypt_oaep::<Sha3_256>(&private_key, b"", &sha3_256).unwrap(), b"pkc OAEP test vector");
+        assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"", &sha384), Err(RsaError::Decryption));
     }
-}
\ No newline at end of file
+
+    //the RSA-2048 key of BoringSSL's crypto/evp/evp_tests.txt
+    fn boringssl_key() -> RsaPrivateKey {
+        let p = from_hex("f6f961b47cbc035d3aedebc7de850a956b65ecdb9cf60764063f15aa48553c58d972fe6675056e35ddfdc37bf3b9f2f622ee271337256849c9bef2176fe8f7c3f8bb91ba374dd53baf3dec814d2bdec10c1fdc88cdd16876f26b1edfa3f094197edf4d42ff1fb2971103b898ca859c427287086a842ab410bb69cf2d35af6be3");
+        let q = from_hex("d47e724a7ff41048b270c2524a4101878b73159bb73d3dbc187b220e635b3534f96e243a184d93f860b6bfbb6b71c1ed9a1e1f458583023c301e96a692c1a08b53d0ec9ca910100d80451e3b7dc6a01bac4aecef8df798846bc235a08cbba2cf4c06804cc11219e95608c714e3f1430d491fadbba32a5751a04f97745834c9a5");
+        let d = from_hex("60297ac7991b167a06d6b24758b8cbe208beb9b2d9ec9738bd80f90a2e35005dd7ce292d9e29ba885bd316fef1f20913bc0ac90d6b0808b2414d82104441d8624a33ce0233c8f780a48b375aff02d76712228a702484db3f9ebecccfbbee1709dba182800d949e9e4216e0bff3558388f8bd90da373a1d82743ec3fbdd1427fd16825a657a316912e8695365117ca2f845c909405fcac55f895fc15d20386c26ee78c9e99075029a178a6c1e4cf0c200e8a9cfb27e9d156f86e6c2adc22b1a84a1cd5ca5b2790875d79407c84b352395cb81cc3fed5bb043b69ede0c07204550025cee8c5f440170b6120bb48e0f747bcd8f522110850df043c428dfd1870531");
+
+        RsaPrivateKey::from_components(p, q, BigInt::from(65537), d).unwrap()
+    }
+
+    #[test]
+    fn test_oaep_boringssl_vectors() {
+        //RSA-2048 OAEP cases of BoringSSL's crypto/evp/evp_tests.txt: SHA-1 with no label,
+        //SHA-512 (label hash and MGF1) with a label, and the first one with its last
+        //ciphertext byte changed
+        let private_key = boringssl_key();
+
+        let sha1 = hex::decode("458708dfbd42a1297ce7a9c86c7087ab80b1754810929b89c5107ca55368587686986fce94d86cc1595b3fb736223a656ec0f34d18ba1cc5665593610f56c58e26b272d584f3d983a5c91085700755aebd921fb280bba3eda7046ec07b43e7298e52d59edc92be4639a8ce08b2f85976ecf6d98cc469eeb9d5d8e2a32ea8a6626edafe1038b3df455668a9f3c77cad8b92fb872e00058c3d2a7ede1a1f03fc5622084ae04d9d24f6bf0995c58d35b93b699b9763595e123f2ab0863cc9229eb290e2ede7715c7a8f39e0b9a3e2e1b56ebb62f1cbfbb5986fb212ebd785b83d01d968b11d1756c7337f70c1f1a63bff03608e24f3a2fd44e67f832a8701c5d5af").unwrap();
+        assert_eq!(decrypt_oaep::<Sha1>(&private_key, b"", &sha1).unwrap(), b"Hello World");
+
+        let label = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let sha512 = hex::decode("48b956c22b8e40cc38f0893672ddf488fc806cf1fcc6239c66dd8345eb543d6b5cac589e6c7ae86dac1c2436c4d72c48009a737b2c649e6000dbab17203e4d9c078bd70b649700a0830d4ddc396af0c48973177a229e48259d93247f04f76474c7611b530c66f020c4da2cc861c2e4104831ecc0336e0cb10d6520fdefd0b33606f5cdd736dd439583b9b6011cce99623c93caf5f76e21e9fefab414795dd5ac12cba551be74ebf266834fcffab182c5e7c9b6c064df154cb26ddfd4fe2fd87590005f4bf45e776a0082803e9f68995b8eeb4c6802c67b5ef349e5b2dc0cf7a12fc097030f2bd28f0253f17129b04c82993a12957728b35880fdd2f8d0cc469f").unwrap();
+        assert_eq!(decrypt_oaep::<Sha512>(&private_key, &label, &sha512).unwrap(), b"Hello World");
+        assert_eq!(decrypt_oaep::<Sha512>(&private_key, b"", &sha512), Err(RsaError::Decryption));
+
+        let corrupted = hex::decode("458708dfbd42a1297ce7a9c86c7087ab80b1754810929b89c5107ca55368587686986fce94d86cc1595b3fb736223a656ec0f34d18ba1cc5665593610f56c58e26b272d584f3d983a5c91085700755aebd921fb280bba3eda7046ec07b43e7298e52d59edc92be4639a8ce08b2f85976ecf6d98cc469eeb9d5d8e2a32ea8a6626edafe1038b3df455668a9f3c77cad8b92fb872e00058c3d2a7ede1a1f03fc5622084ae04d9d24f6bf0995c58d35b93b699b9763595e123f2ab0863cc9229eb290e2ede7715c7a8f39e0b9a3e2e1b56ebb62f1cbfbb5986fb212ebd785b83d01d968b11d1756c7337f70c1f1a63bff03608e24f3a2fd44e67f832a8701c5d5ac").unwrap();
+        assert_eq!(decrypt_oaep::<Sha1>(&private_key, b"", &corrupted), Err(RsaError::Decryption));
+    }
+
+    #[test]
+    fn test_oaep_rfc8017_vectors() {
+        //RSAES-OAEP example 10 of the PKCS #1 v2.1 test vectors (oaep-vect.txt), the
+        //2048-bit key, SHA-1 for both the label hash and MGF1 and an empty label
+        let p = from_hex("ecf5aecd1e5515fffacbd75a2816c6ebf49018cdfb4638e185d66a7396b6f8090f8018c7fd95cc34b857dc17f0cc6516bb1346ab4d582cadad7b4103352387b70338d084047c9d9539b6496204b3dd6ea442499207bec01f964287ff6336c3984658336846f56e46861881c10233d2176bf15a5e96ddc780bc868aa77d3ce769");
+        let q = from_hex("bc46c464fc6ac4ca783b0eb08a3c841b772f7e9b2f28babd588ae885e1a0c61e4858a0fb25ac299990f35be85164c259ba1175cdd7192707135184992b6c29b746dd0d2cabe142835f7d148cc161524b4a09946d48b828473f1ce76b6cb6886c345c03e05f41d51b5c3a90a3f24073c7d74a4fe25d9cf21c75960f3fc3863183");
+        let d = from_hex("56b04216fe5f354ac77250a4b6b0c8525a85c59b0bd80c56450a22d5f438e596a333aa875e291dd43f48cb88b9d5fc0d499f9fcd1c397f9afc070cd9e398c8d19e61db7c7410a6b2675dfbf5d345b804d201add502d5ce2dfcb091ce9997bbebe57306f383e4d588103f036f7e85d1934d152a323e4a8db451d6f4a5b1b0f102cc150e02feee2b88dea4ad4c1baccb24d84072d14e1d24a6771f7408ee30564fb86d4393a34bcf0b788501d193303f13a2284b001f0f649eaf79328d4ac5c430ab4414920a9460ed1b7bc40ec653e876d09abc509ae45b525190116a0c26101848298509c1c3bf3a483e7274054e15e97075036e989f60932807b5257751e79");
+        let private_key = RsaPrivateKey::from_components(p, q, BigInt::from(65537), d).unwrap();
+
+        let vectors: [(&str, &str); 6] = [
+            ("8bba6bf82a6c0f86d5f1756e97956870b08953b06b4eb205bc1694ee", "53ea5dc08cd260fb3b858567287fa91552c30b2febfba213f0ae87702d068d19bab07fe574523dfb42139d68c3c5afeee0bfe4cb7969cbf382b804d6e61396144e2d0e60741f8993c3014b58b9b1957a8babcd23af854f4c356fb1662aa72bfcc7e586559dc4280d160c126785a723ebeebeff71f11594440aaef87d10793a8774a239d4a04c87fe1467b9daf85208ec6c7255794a96cc29142f9a8bd418e3c1fd67344b0cd0829df3b2bec60253196293c6b34d3f75d32f213dd45c6273d505adf4cced1057cb758fc26aeefa441255ed4e64c199ee075e7f16646182fdb464739b68ab5daff0e63e9552016824f054bf4d3c8c90a97bb6b6553284eb429fcc"),
+            ("e6ad181f053b58a904f2457510373e57", "a2b1a430a9d657e2fa1c2bb5ed43ffb25c05a308fe9093c01031795f5874400110828ae58fb9b581ce9dddd3e549ae04a0985459bde6c626594e7b05dc4278b2a1465c1368408823c85e96dc66c3a30983c639664fc4569a37fe21e5a195b5776eed2df8d8d361af686e750229bbd663f161868a50615e0c337bec0ca35fec0bb19c36eb2e0bbcc0582fa1d93aacdb061063f59f2ce1ee43605e5d89eca183d2acdfe9f81011022ad3b43a3dd417dac94b4e11ea81b192966e966b182082e71964607b4f8002f36299844a11f2ae0faeac2eae70f8f4f98088acdcd0ac556e9fccc511521908fad26f04c64201450305778758b0538bf8b5bb144a828e629795"),
+            ("510a2cf60e866fa2340553c94ea39fbc256311e83e94454b4124", "9886c3e6764a8b9a84e84148ebd8c3b1aa8050381a78f668714c16d9cfd2a6edc56979c535d9dee3b44b85c18be8928992371711472216d95dda98d2ee8347c9b14dffdff84aa48d25ac06f7d7e65398ac967b1ce90925f67dce049b7f812db0742997a74d44fe81dbe0e7a3feaf2e5c40af888d550ddbbe3bc20657a29543f8fc2913b9bd1a61b2ab2256ec409bbd7dc0d17717ea25c43f42ed27df8738bf4afc6766ff7aff0859555ee283920f4c8a63c4a7340cbafddc339ecdb4b0515002f96c932b5b79167af699c0ad3fccfdf0f44e85a70262bf2e18fe34b850589975e867ff969d48eabf212271546cdc05a69ecb526e52870c836f307bd798780ede"),
+            ("bcdd190da3b7d300df9a06e22caae2a75f10c91ff667b7c16bde8b53064a2649a94045c9", "6318e9fb5c0d05e5307e1683436e903293ac4642358aaa223d7163013aba87e2dfda8e60c6860e29a1e92686163ea0b9175f329ca3b131a1edd3a77759a8b97bad6a4f8f4396f28cf6f39ca58112e48160d6e203daa5856f3aca5ffed577af499408e3dfd233e3e604dbe34a9c4c9082de65527cac6331d29dc80e0508a0fa7122e7f329f6cca5cfa34d4d1da417805457e008bec549e478ff9e12a763c477d15bbb78f5b69bd57830fc2c4ed686d79bc72a95d85f88134c6b0afe56a8ccfbc855828bb339bd17909cf1d70de3335ae07039093e606d655365de6550b872cd6de1d440ee031b61945f629ad8a353b0d40939e96a3c450d2a8d5eee9f678093c8"),
+            ("a7dd6c7dc24b46f9dd5f1e91ada4c3b3df947e877232a9", "75290872ccfd4a4505660d651f56da6daa09ca1301d890632f6a992f3d565cee464afded40ed3b5be9356714ea5aa7655f4a1366c2f17c728f6f2c5a5d1f8e28429bc4e6f8f2cff8da8dc0e0a9808e45fd09ea2fa40cb2b6ce6ffff5c0e159d11b68d90a85f7b84e103b09e682666480c657505c0929259468a314786d74eab131573cf234bf57db7d9e66cc6748192e002dc0deea930585f0831fdcd9bc33d51f79ed2ffc16bcf4d59812fcebcaa3f9069b0e445686d644c25ccf63b456ee5fa6ffe96f19cdf751fed9eaf35957754dbf4bfea5216aa1844dc507cb2d080e722eba150308c2b5ff1193620f1766ecf4481bafb943bd292877f2136ca494aba0"),
+            ("eaf1a73a1b0c4609537de69cd9228bbcfb9a8ca8c6c3efaf056fe4a7f4634ed00b7c39ec6922d7b8ea2c04ebac", "2d207a73432a8fb4c03051b3f73b28a61764098dfa34c47a20995f8115aa6816679b557e82dbee584908c6e69782d7deb34dbd65af063d57fca76a5fd069492fd6068d9984d209350565a62e5c77f23038c12cb10c6634709b547c46f6b4a709bd85ca122d74465ef97762c29763e06dbc7a9e738c78bfca0102dc5e79d65b973f28240caab2e161a78b57d262457ed8195d53e3c7ae9da021883c6db7c24afdd2322eac972ad3c354c5fcef1e146c3a0290fb67adf007066e00428d2cec18ce58f9328698defef4b2eb5ec76918fde1c198cbb38b7afc67626a9aefec4322bfd90d2563481c9a221f78c8272c82d1b62ab914e1c69f6af6ef30ca5260db4a46"),
+        ];
+
+        for (msg, ciphertext) in vectors {
+            let ciphertext = hex::decode(ciphertext).unwrap();
+            assert_eq!(decrypt_oaep::<Sha1>(&private_key, b"", &ciphertext).unwrap(), hex::decode(msg).unwrap());
+        }
+    }
+
+    //the test key as a key of the RustCrypto rsa crate
+    fn rustcrypto_key() -> rsa::RsaPrivateKey {
+        let to_biguint = |hex_str: &str| rsa::BigUint::from_bytes_be(&hex::decode(hex_str).unwrap());
+        let (p, q) = (to_biguint(TEST_P), to_biguint(TEST_Q));
+
+        rsa::RsaPrivateKey::from_components(&p * &q, rsa::BigUint::from(65537u32), to_biguint(TEST_D), vec![p, q]).unwrap()
+    }
+
+    #[test]
+    fn test_oaep_rustcrypto_interop() {
+        //the RustCrypto rsa crate is an OAEP implementation independent of both this one
+        //and OpenSSL. ciphertexts from either side have to decrypt on the other
+        let mut rng = rand::rng();
+        let mut os_rng = rsa::rand_core::OsRng;
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+        let other = rustcrypto_key();
+
+        for len in [0, 1, 20, 190] {
+            let msg: Vec<u8> = (0..len).map(|i| i as u8).collect();
+
+            let ciphertext = other.to_public_key().encrypt(&mut os_rng, rsa::Oaep::new::<Sha256>(), &msg).unwrap();
+            assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"", &ciphertext).unwrap(), msg);
+
+            let ciphertext = other.to_public_key().encrypt(&mut os_rng, rsa::Oaep::new_with_label::<Sha256, _>("pkc label"), &msg).unwrap();
+            assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"pkc label", &ciphertext).unwrap(), msg);
+            assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"", &ciphertext), Err(RsaError::Decryption));
+
+            let ciphertext = encrypt_oaep::<Sha256, _>(public_key, b"pkc label", &msg, &mut rng).unwrap();
+            assert_eq!(other.decrypt(rsa::Oaep::new_with_label::<Sha256, _>("pkc label"), &ciphertext).unwrap(), msg);
+        }
+
+        //other hash functions, with the same hash for the label and MGF1
+        let msg = b"pkc OAEP test vector";
+        let ciphertext = other.to_public_key().encrypt(&mut os_rng, rsa::Oaep::new::<Sha384>(), msg).unwrap();
+        assert_eq!(decrypt_oaep::<Sha384>(&private_key, b"", &ciphertext).unwrap(), msg);
+        let ciphertext = encrypt_oaep::<Sha3_256, _>(public_key, b"", msg, &mut rng).unwrap();
+        assert_eq!(other.decrypt(rsa::Oaep::new::<Sha3_256>(), &ciphertext).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_oaep_round_trip() {
+        let mut rng = rand::rng();
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+
+        for len in [0, 1, 32, 190] {
+            let msg: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let ciphertext = encrypt_oaep::<Sha256, _>(public_key, b"label", &msg, &mut rng).unwrap();
+
+            assert_eq!(ciphertext.len(), 256);
+            assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"label", &ciphertext).unwrap(), msg);
+
+            //encryption is randomised
+            assert_ne!(encrypt_oaep::<Sha256, _>(public_key, b"label", &msg, &mut rng).unwrap(), ciphertext);
+        }
+
+        assert_eq!(encrypt_oaep::<Sha256, _>(public_key, b"", &[0u8; 191], &mut rng), Err(RsaError::MessageTooLong));
+
+        //longer hashes leave less room: k - 2*hLen - 2 = 126 bytes for SHA-512
+        let ciphertext = encrypt_oaep::<Sha512, _>(public_key, b"label", &[0x5a; 126], &mut rng).unwrap();
+        assert_eq!(decrypt_oaep::<Sha512>(&private_key, b"label", &ciphertext).unwrap(), [0x5a; 126]);
+        assert_eq!(decrypt_oaep::<Sha3_384>(&private_key, b"label", &ciphertext), Err(RsaError::Decryption));
+        assert_eq!(encrypt_oaep::<Sha512, _>(public_key, b"", &[0u8; 127], &mut rng), Err(RsaError::MessageTooLong));
+
+        let ciphertext = encrypt_oaep::<Sha3_384, _>(public_key, b"", b"message", &mut rng).unwrap();
+        assert_eq!(decrypt_oaep::<Sha3_384>(&private_key, b"", &ciphertext).unwrap(), b"message");
+    }
+
+    #[test]
+    fn test_oaep_decryption_errors_are_opaque() {
+        let mut rng = rand::rng();
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+        let ciphertext = encrypt_oaep::<Sha256, _>(public_key, b"label", b"message", &mut rng).unwrap();
+
+        //wrong label
+        assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"other", &ciphertext), Err(RsaError::Decryption));
+
+        //wrong length
+        assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"label", &ciphertext[1..]), Err(RsaError::Decryption));
+
+        //ciphertext representative >= n
+        assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"label", &[0xff; 256]), Err(RsaError::Decryption));
+
+        //tampered ciphertext
+        let mut tampered = ciphertext.clone();
+        tampered[100] ^= 0x01;
+        assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"label", &tampered), Err(RsaError::Decryption));
+
+        //valid RSA, broken padding: leading byte, missing separator, garbage in PS
+        let k = public_key.size();
+        let mut em = vec![0u8; k];
+        em[0] = 0x01;
+        let bad_leading_byte = i2osp(&public_key.encrypt(&os2ip(&em)).unwrap(), k).unwrap();
+        assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"", &bad_leading_byte), Err(RsaError::Decryption));
+
+        for db_byte in [0x00, 0x02] {
+            let mut seed = vec![0u8; 32];
+            let mut db = vec![0u8; k - 33];
+            db[..32].copy_from_slice(&Sha256::digest(b""));
+            db[40] = db_byte;
+            xor_in_place(&mut db, &mgf1::<Sha256>(&seed, k - 33));
+            xor_in_place(&mut seed, &mgf1::<Sha256>(&db, 32));
+
+            let mut em = vec![0u8];
+            em.extend_from_slice(&seed);
+            em.extend_from_slice(&db);
+            let bad_padding = i2osp(&public_key.encrypt(&os2ip(&em)).unwrap(), k).unwrap();
+            assert_eq!(decrypt_oaep::<Sha256>(&private_key, b"", &bad_padding), Err(RsaError::Decryption));
+        }
+    }
+
+    //RSAEP of an EME-OAEP (SHA-256) encoding built from its parts, so decryption can be
+    //fed encodings that encrypt_oaep would never produce. rest is DB after lHash
+    fn raw_oaep_ciphertext(public_key: &RsaPublicKey, leading_byte: u8, l_hash: &[u8], rest: &[u8]) -> Vec<u8> {
+        let k = public_key.size();
+        let mut seed = vec![0x5a; 32];
+        let mut db = [l_hash, rest].concat();
+        assert_eq!(db.len(), k - 33);
+
+        xor_in_place(&mut db, &mgf1::<Sha256>(&seed, k - 33));
+        xor_in_place(&mut seed, &mgf1::<Sha256>(&db, 32));
+        let em = [&[leading_byte][..], &seed, &db].concat();
+
+        i2osp(&public_key.encrypt(&os2ip(&em)).unwrap(), k).unwrap()
+    }
+
+    #[test]
+    fn test_oaep_invalid_padding() {
+        //modelled on the invalid-padding cases of Wycheproof's
+        //rsa_oaep_2048_sha256_mgf1sha256 file, built on the test key rather than copied.
+        //every one has to give the same error
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+        let k = public_key.size();
+        let l_hash = Sha256::digest(b"").to_vec();
+        let decrypt = |ciphertext: &[u8]| decrypt_oaep::<Sha256>(&private_key, b"", ciphertext);
+
+        //rest = PS || 0x01 || M for a message of len bytes
+        let padded = |msg: &[u8]| [vec![0u8; k - 66 - msg.len()], vec![0x01], msg.to_vec()].concat();
+
+        //well formed encodings decrypt, including an empty and a maximum length message
+        for msg in [&b"message"[..], &[], &[0xab; 190]] {
+            assert_eq!(decrypt(&raw_oaep_ciphertext(public_key, 0x00, &l_hash, &padded(msg))).unwrap(), msg);
+        }
+
+        let mut bad_l_hash_first = l_hash.clone();
+        bad_l_hash_first[0] ^= 0x01;
+        let mut bad_l_hash_last = l_hash.clone();
+        bad_l_hash_last[31] ^= 0x80;
+
+        let mut no_separator = padded(b"message");
+        no_separator[k - 66 - 7] = 0x00;
+        let mut separator_02 = padded(b"message");
+        separator_02[k - 66 - 7] = 0x02;
+        let mut separator_ff = padded(b"message");
+        separator_ff[k - 66 - 7] = 0xff;
+        let mut nonzero_ps_first = padded(b"message");
+        nonzero_ps_first[0] = 0x02;
+        let mut nonzero_ps_last = padded(b"message");
+        nonzero_ps_last[k - 66 - 8] = 0x80;
+
+        let invalid_encodings: [(u8, &[u8], &[u8]); 12] = [
+            (0x01, &l_hash, &padded(b"message")),
+            (0x02, &l_hash, &padded(b"message")),
+            (0x80, &l_hash, &padded(b"message")),
+            (0x00, &bad_l_hash_first, &padded(b"message")),
+            (0x00, &bad_l_hash_last, &padded(b"message")),
+            (0x00, &Sha256::digest(b"label"), &padded(b"message")),
+            (0x00, &l_hash, &no_separator),
+            (0x00, &l_hash, &vec![0u8; k - 65]),
+            (0x00, &l_hash, &separator_02),
+            (0x00, &l_hash, &separator_ff),
+            (0x00, &l_hash, &nonzero_ps_first),
+            (0x00, &l_hash, &nonzero_ps_last),
+        ];
+        for (leading_byte, l_hash, rest) in invalid_encodings {
+            assert_eq!(decrypt(&raw_oaep_ciphertext(public_key, leading_byte, l_hash, rest)), Err(RsaError::Decryption));
+        }
+
+        //ciphertexts that aren't k byte representatives of an integer below n
+        let valid = raw_oaep_ciphertext(public_key, 0x00, &l_hash, &padded(b"message"));
+        let n = i2osp(public_key.n(), k).unwrap();
+        let n_minus_one = i2osp(&(public_key.n() - BigInt::from(1)), k).unwrap();
+        let invalid_ciphertexts: [Vec<u8>; 8] = [
+            vec![],
+            vec![0u8; k],
+            n_minus_one,
+            n,
+            vec![0xff; k],
+            [&[0x00][..], &valid].concat(),
+            valid[1..].to_vec(),
+            valid[..k - 1].to_vec(),
+        ];
+        for ciphertext in invalid_ciphertexts {
+            assert_eq!(decrypt(&ciphertext), Err(RsaError::Decryption));
+        }
+    }
+
+    const SIGNED_MESSAGE: &[u8] = b"pkc signature test vector";
+
+    #[test]
+    fn test_pkcs1v15_openssl_vectors() {
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+
+        //deterministic signatures of SIGNED_MESSAGE produced by OpenSSL under the test key
+        let sha256 = hex::decode("ab1e5043b7782bf5823a6ca37800aae3892904d2d5c18e2079bad56478462971d7c24b1cc26726f670f65466aa783044967a2f944f5875e7c93dae4810c31bb945b6979cf1f930b5cf213bf1b88b6a4e86a04b6da0b5e914fd20153aa7b03b59789ec008d1e80b1c7d1629c43347579ec852ea2c3b40ec4efe6e61b8d412cade86f07579cc5a3efd11802ae247956a9b28c499d77ada8eb052c21026d90a7f8188765af60848c625d0f6582c0ad32777bd2a324a4aa5419c6a7a92b0fd2f6656ffbfa63c884905657af1e0cead4674d08de148eccf2dd65a1c35475ab2c5463a6708ba61e4fedb2e56722c55d547f1e8fdfa9c1b19ff3f943beb2ed2e8be7f68").unwrap();
+        let sha384 = hex::decode("38dac51a8b9cab039da214301493c3d3a492705f0f74363ebb7fee3e380bd16cbc3b0b84f8e135cb9dd1984de254b3bcd996b87379a0af7ac1e36038fdad1b0f049ddd1387859ce19979890a851cbe513b1ffc1403a1139fa80b9dc359d6bc1619a1c86f4f1a9218b025c052e12cbe83de9cafd60cacfea884a1babe6fc7fe101484e1eb767b25320de959048a838dbab1d035464fd4e6f3a30e786e7eb861ccdee3fd4faf376e9c0be4d75831f0ba02c85e893fc2d0588d5d8ad90eb58a81380d09e8eb99b683038df173c25af56b7a3d7228186cef7765bdcaf061a2bb33b8ea0ba88787dcd15a05fe5d83c61a107548333da3555814684a5e541982925d92").unwrap();
+        let sha512 = hex::decode("062ac4116efaf323511eb4eec2bdc1780a961ef2036e7b5f45ea5cd0ea552e65f0dc0c2140b5a0d99e936097ade3ce3a3713cbda7a927614145a6ce6057d79bbc068c4c8aee80332c3d4a47537219088bb61733e8ea08a4d88f494ecf8389cf02cfc8b2de11a303e01630b2a30687f0f4df0509507e24fd754e0491c830db87022ef8acaa993ede72a906885d52e1eed4991261053a1d304c79ce93a8dd75a605fb278446c118bd1d0ad8ccee9a7b246edba65a638b3b9507692c17706dcf2df2f01b8bc1ca6ef6b836fd011ce0e75b9600cc27f509cc18e5d306ea4c8e213d2102a7931c08a5886c8121a2fd276633ed8b35bd1fc29293ee45b27606210f960").unwrap();
+
+        assert_eq!(sign_pkcs1v15::<Sha256>(&private_key, SIGNED_MESSAGE).unwrap(), sha256);
+        assert_eq!(sign_pkcs1v15::<Sha384>(&private_key, SIGNED_MESSAGE).unwrap(), sha384);
+        assert_eq!(sign_pkcs1v15::<Sha512>(&private_key, SIGNED_MESSAGE).unwrap(), sha512);
+
+        assert_eq!(verify_pkcs1v15::<Sha256>(public_key, SIGNED_MESSAGE, &sha256), Ok(()));
+        assert_eq!(verify_pkcs1v15::<Sha384>(public_key, SIGNED_MESSAGE, &sha384), Ok(()));
+        assert_eq!(verify_pkcs1v15::<Sha512>(public_key, SIGNED_MESSAGE, &sha512), Ok(()));
+
+        //prehashed entry points agree with hashing the message
+        let digest = Sha256::digest(SIGNED_MESSAGE);
+        assert_eq!(sign_pkcs1v15_prehashed::<Sha256>(&private_key, &digest).unwrap(), sha256);
+        assert_eq!(verify_pkcs1v15_prehashed::<Sha256>(public_key, &digest, &sha256), Ok(()));
+        let hasher = Sha256::new_with_prefix(&SIGNED_MESSAGE[..8]).chain_update(&SIGNED_MESSAGE[8..]);
+        assert_eq!(sign_pkcs1v15_digest(&private_key, hasher.clone()).unwrap(), sha256);
+        assert_eq!(verify_pkcs1v15_digest(public_key, hasher, &sha256), Ok(()));
+
+        //wrong hash, message or signature
+        assert_eq!(verify_pkcs1v15::<Sha384>(public_key, SIGNED_MESSAGE, &sha256), Err(RsaError::Verification));
+        assert_eq!(verify_pkcs1v15::<Sha256>(public_key, b"other message", &sha256), Err(RsaError::Verification));
+        let mut tampered = sha256.clone();
+        tampered[0] ^= 0x80;
+        assert_eq!(verify_pkcs1v15::<Sha256>(public_key, SIGNED_MESSAGE, &tampered), Err(RsaError::Verification));
+        assert_eq!(verify_pkcs1v15::<Sha256>(public_key, SIGNED_MESSAGE, &sha256[1..]), Err(RsaError::Verification));
+        assert_eq!(sign_pkcs1v15_prehashed::<Sha256>(&private_key, &digest[1..]), Err(RsaError::InvalidDigestLength));
+    }
+
+    #[test]
+    fn test_pss_openssl_vectors() {
+        let public_key = test_key().public_key().clone();
+
+        //randomised signatures of SIGNED_MESSAGE produced by OpenSSL under the test key
+        let sha256_salt_32 = hex::decode("6d3204449df0b624cf46df56bb1c997b1d31ab3ac1fffc289215797089b00749223673053466a0491e6865bc4db83e476428d2fa8fd69489aaf788ad0f6c2a6112df3b4a04252581cfdff2ebebba5224cb8d19e04b5ac092bacea6925c0de54d7cdf7fa239562e209a0f017ce11dc95e824cc65fc6903060a58c394add3ed698623b8ffdfeb224410a35b824ede4e58793b46226634f583bb146217018fd883a7c77adecf965ee7b3e2c10b7f2fb6bdf4546a64385babd0e95a54d7768500e59c01cd685aff958e96a31ba987124b456ca29fa0d2721579ea7a6815ca9ec6fc10225eed457f618382bbc776767e0f01f6276257299af69c2de895283f8e6567d").unwrap();
+        let sha384_salt_48 = hex::decode("b47516c0e8e81e1af6fe203460cdf51aaf36606cd3bf61b10724058fdbaad0c2a6c3c4f57e5b22710ded538441928e1b26584844b215531913bc4a8f127d647af12af7c8ac1731df2deeb317e7b0eda853ee4a7a27022299087536a2dc2b0407acf74e634649d19e8cc54b99a51f7ef055d065b6bb766649fbd7d5274cd6b12d931988ae7c66c2c7979d0e67e4bc5dd0cc6c16929b26b1d208c574fb4f5bc4ecf931c3084a1f97722e2846e15c7c5ca98770194e3b0c75b999733d401db6bf1231fe739dda7ec34eb32f7fd938b6f1cf45af765417ea492da0be7c20cbaa5edcc4f13e39aac3afa08b8bfe4930404eed4be97236915c14e341b5b07ab700888e").unwrap();
+
+        assert_eq!(verify_pss::<Sha256>(&public_key, SIGNED_MESSAGE, &sha256_salt_32, 32), Ok(()));
+        assert_eq!(verify_pss::<Sha384>(&public_key, SIGNED_MESSAGE, &sha384_salt_48, 48), Ok(()));
+
+        assert_eq!(verify_pss::<Sha256>(&public_key, SIGNED_MESSAGE, &sha256_salt_32, 20), Err(RsaError::Verification));
+        assert_eq!(verify_pss::<Sha384>(&public_key, SIGNED_MESSAGE, &sha256_salt_32, 32), Err(RsaError::Verification));
+        assert_eq!(verify_pss::<Sha256>(&public_key, b"other message", &sha256_salt_32, 32), Err(RsaError::Verification));
+
+        //SHA3-256 and SHA-512/256 for both the message hash and MGF1, 32 byte salt
+        let sha3_256 = hex::decode("68984eb5222f5f3d90f3080423b4d7aae20f9916211fa03f977941d3003c1b9535d9106d94f92898608310107b404f25d474a5a208aa70a46ff0158c832b2a7042cef659be4580ac0ea16602300ca6f44fccf6cf0d6e47829960c24a1f7312170a2060fa3fe7d8df3ffc6a41eba2a7ef2e85f2e306a37b1d732e4bdaa7f0daf4668735f1c1f73e57434c4c123ad45b82c93d530af1bfcba59cc37f6e5be41d5526eb26f136f61caa2b0ddd4d348e324f89cd2f68bc79fa1af628e9041780495415685971c28454874152023944ffaab4c4cf9713661cf8110f732f6e083ac02fb7c8ac53e1c1a7e548dab066b165f104bd9d2dd618273dc0c57767bfc059023d").unwrap();
+        let sha512_256 = hex::decode("87437d23c15cb28599ad766fdb3e95a70c08ced2e298f67b32120ab69a9702b4245057af86f99c499551e8b123e4942fe5168b2a7c58715e3e27070419d4e2a6df33471a8b6c70245f7bbc200ea4ba34217c08a1c8f8e5e1d3e06e0d400b0c36c09e75906c40c02478855397499530abca55fdb860b3f2507dee8d70266e11c72ef306860fad10e16f292a26eff2022b2f9a5463865fd125ab4a57ccb806ad8650796a0454fdfeb06dacaf61ef69a74ce9d62a3d460d6a3a9e2fb218a90d257ab1ea0c1614e6ce73c77ddde26ae1c6510c298decfb2257d3f99d2781324f5603871795948b0d2d24bc530130e34b8cca02c1b43e870e7ad8e53553627a04e08f").unwrap();
+        assert_eq!(verify_pss::<Sha3_256>(&public_key, SIGNED_MESSAGE, &sha3_256, 32), Ok(()));
+        assert_eq!(verify_pss::<Sha512_256>(&public_key, SIGNED_MESSAGE, &sha512_256, 32), Ok(()));
+        assert_eq!(verify_pss::<Sha256>(&public_key, SIGNED_MESSAGE, &sha3_256, 32), Err(RsaError::Verification));
+    }
+
+    #[test]
+    fn test_pss_zero_salt_is_deterministic() {
+        let mut rng = rand::rng();
+        let private_key = test_key();
+
+        //with an empty salt PSS is deterministic, so this OpenSSL signature can be reproduced
+        let expected = hex::decode("136128dd975339718002572e019ca9ef29d5299a8e9eed478b62a3e3ba8f06180d09f4a6afd0fee4250a87c41f429b732368698791f5f411abe7e5d22544d76f6fdd72575ade5602189619396b8f718f7e44bb69cb579da59024b7be3781bf46f61d6fc767cc8714830644e4dd2a37bea14d3c77e4100d060f21a3fb06642ec873be4ffe051f046ff1083fbf495e2d390f28fe88b1c5c84016087b751947dbf26f85cbe26fd9b68c446d47ba6ab72d5ee0a9690a6134f93786595d3b99e8f6e5c4ae7197bb8521cdad2945122abde89e1b24dcd1230a41d05cd6400de2a12838b3b22ce230cce45477ef6557b948a4bf25052e6aed3b53644bee124b6c604f62").unwrap();
+
+        assert_eq!(sign_pss::<Sha256, _>(&private_key, SIGNED_MESSAGE, 0, &mut rng).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pss_round_trip() {
+        let mut rng = rand::rng();
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+
+        for salt_len in [0, 20, 32, 64] {
+            let signature = sign_pss::<Sha256, _>(&private_key, SIGNED_MESSAGE, salt_len, &mut rng).unwrap();
+            assert_eq!(verify_pss::<Sha256>(public_key, SIGNED_MESSAGE, &signature, salt_len), Ok(()));
+
+            let digest = Sha512::digest(SIGNED_MESSAGE);
+            let signature = sign_pss_prehashed::<Sha512, _>(&private_key, &digest, salt_len, &mut rng).unwrap();
+            assert_eq!(verify_pss_prehashed::<Sha512>(public_key, &digest, &signature, salt_len), Ok(()));
+            assert_eq!(verify_pss::<Sha512>(public_key, SIGNED_MESSAGE, &signature, salt_len), Ok(()));
+
+            let hasher = Sha384::new_with_prefix(&SIGNED_MESSAGE[..8]).chain_update(&SIGNED_MESSAGE[8..]);
+            let signature = sign_pss_digest(&private_key, hasher.clone(), salt_len, &mut rng).unwrap();
+            assert_eq!(verify_pss_digest(public_key, hasher, &signature, salt_len), Ok(()));
+            assert_eq!(verify_pss::<Sha384>(public_key, SIGNED_MESSAGE, &signature, salt_len), Ok(()));
+        }
+
+        //salt must leave room for the hash and two bytes of framing in the 256 byte EM
+        let signature = sign_pss::<Sha256, _>(&private_key, SIGNED_MESSAGE, 222, &mut rng).unwrap();
+        assert_eq!(verify_pss::<Sha256>(public_key, SIGNED_MESSAGE, &signature, 222), Ok(()));
+        assert_eq!(sign_pss::<Sha256, _>(&private_key, SIGNED_MESSAGE, 223, &mut rng), Err(RsaError::InvalidSaltLength));
+    }
+
+    #[test]
+    fn test_rejects_weak_keys() {
+        let mut rng = rand::rng();
+        let e = BigInt::from(DEFAULT_PUBLIC_EXPONENT);
+        let p = from_hex(TEST_P);
+        let q = from_hex(TEST_Q);
+
+        assert_eq!(RsaPrivateKey::from_primes(p.clone(), p.clone(), e.clone()), Err(RsaError::EqualPrimes));
+
+        //p and q too close together
+        let mut close = &p + BigInt::from(2);
+        while !helper::is_prime(&close, 40) {
+            close += BigInt::from(2);
+        }
+        assert_eq!(RsaPrivateKey::from_primes(p.clone(), close, e.clone()), Err(RsaError::PrimesTooClose));
+
+        //composite factor
+        let mut composite = &q + BigInt::from(2);
+        while helper::is_prime(&composite, 40) {
+            composite += BigInt::from(2);
+        }
+        assert_eq!(RsaPrivateKey::from_primes(p.clone(), composite, e.clone()), Err(RsaError::InvalidPrime));
+
+        //small and even public exponents
+        for bad_e in [3, 17, 65536] {
+            assert_eq!(RsaPrivateKey::from_primes(p.clone(), q.clone(), BigInt::from(bad_e)), Err(RsaError::InvalidPublicExponent));
+        }
+
+        //small modulus
+        let small = generate_prime_for_exponent(512, &e, &mut rng);
+        assert_eq!(RsaPrivateKey::from_primes(small, p.clone(), e.clone()), Err(RsaError::ModulusTooSmall));
+        assert_eq!(RsaPrivateKey::generate(1024, &mut rng).unwrap_err(), RsaError::ModulusTooSmall);
+
+        //d that does not invert e
+        let wrong_d = from_hex(TEST_D) + BigInt::from(2);
+        assert_eq!(RsaPrivateKey::from_components(p.clone(), q.clone(), e.clone(), wrong_d), Err(RsaError::InvalidPrivateExponent));
+
+        //factors of at most one, with the other factor large enough to pass the modulus size check
+        let n = &p * &q;
+        for bad in [-1, 0, 1] {
+            assert_eq!(RsaPrivateKey::from_components(BigInt::from(bad), n.clone(), e.clone(), from_hex(TEST_D)), Err(RsaError::InvalidPrime));
+            assert_eq!(RsaPrivateKey::from_components(n.clone(), BigInt::from(bad), e.clone(), from_hex(TEST_D)), Err(RsaError::InvalidPrime));
+        }
+    }
+
+    #[test]
+    fn test_message_out_of_range() {
+        let private_key = test_key();
+        let public_key = private_key.public_key();
+
+        assert_eq!(public_key.encrypt(public_key.n()), Err(RsaError::MessageOutOfRange));
+        assert_eq!(public_key.encrypt(&BigInt::from(-1)), Err(RsaError::MessageOutOfRange));
+        assert_eq!(private_key.decrypt(public_key.n()), Err(RsaError::MessageOutOfRange));
+    }
+}
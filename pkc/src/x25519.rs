@@ -0,0 +1,292 @@
+use crypto_bigint::{Encoding, Odd, U256};
+use crypto_bigint::modular::{MontyForm, MontyParams};
+use rand::CryptoRng;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use std::fmt;
+use zeroize::Zeroize;
+
+
+//X25519 Diffie-Hellman (RFC 7748)
+//
+//works on the Montgomery curve Curve25519: v^2 = u^3 + 486662u^2 + u over
+//GF(2^255 - 19) with x-only (u coordinate) ladder arithmetic. scalars and u
+//coordinates are 32-byte little-endian strings
+
+///p = 2^255 - 19
+const P: U256 = U256::from_be_hex("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed");
+
+///(A - 2) / 4 for A = 486662
+const A24: u64 = 121665;
+
+///u coordinate of the base point, u = 9
+pub const BASEPOINT: [u8; 32] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+];
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum X25519Error {
+    LowOrderPoint,
+}
+
+impl fmt::Display for X25519Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            X25519Error::LowOrderPoint => write!(f, "peer public key is a low order point, shared secret is all zero"),
+        }
+    }
+}
+
+impl std::error::Error for X25519Error {}
+
+
+///x25519() computes the u coordinate of k*P, where k is the clamped scalar and P
+///is a point with u coordinate u (RFC 7748 section 5)
+///
+/// input:
+///     - scalar = 32 random bytes, clamped before use
+///     - u = 32-byte u coordinate. the top bit is ignored and non-canonical values
+///       (p <= u < 2^255) are accepted and reduced mod p
+///
+/// output:
+///     - 32-byte u coordinate of the result
+///
+///the ladder always runs 255 steps with constant-time swaps, so timing doesn't
+///depend on the scalar
+pub fn x25519(scalar: [u8; 32], u: [u8; 32]) -> [u8; 32] {
+    let params = MontyParams::new_vartime(Odd::new(P).unwrap());
+    let k = clamp(scalar);
+
+    let mut u = u;
+    u[31] &= 0x7f;
+    let x_1 = MontyForm::new(&U256::from_le_bytes(u), params);
+    let a24 = MontyForm::new(&U256::from(A24), params);
+
+    let mut x_2 = MontyForm::one(params);
+    let mut z_2 = MontyForm::zero(params);
+    let mut x_3 = x_1;
+    let mut z_3 = MontyForm::one(params);
+    let mut swap = Choice::from(0);
+
+    for t in (0..255).rev() {
+        let k_t = Choice::from((k[t / 8] >> (t % 8)) & 1);
+        swap ^= k_t;
+        MontyForm::conditional_swap(&mut x_2, &mut x_3, swap);
+        MontyForm::conditional_swap(&mut z_2, &mut z_3, swap);
+        swap = k_t;
+
+        //differential addition and doubling (RFC 7748 section 5)
+        let a = x_2 + z_2;
+        let aa = a.square();
+        let b = x_2 - z_2;
+        let bb = b.square();
+        let e = aa - bb;
+        let c = x_3 + z_3;
+        let d = x_3 - z_3;
+        let da = d * a;
+        let cb = c * b;
+        x_3 = (da + cb).square();
+        z_3 = x_1 * (da - cb).square();
+        x_2 = aa * bb;
+        z_2 = e * (aa + a24 * e);
+    }
+
+    MontyForm::conditional_swap(&mut x_2, &mut x_3, swap);
+    MontyForm::conditional_swap(&mut z_2, &mut z_3, swap);
+
+    //x_2 / z_2 with z_2^-1 = z_2^(p-2), which is 0 when z_2 is
+    let z_2_inv = z_2.pow(&P.wrapping_sub(&U256::from(2u64)));
+    (x_2 * z_2_inv).retrieve().to_le_bytes()
+}
+
+//clear the three low bits (multiple of the cofactor 8), clear bit 255 and set bit 254
+fn clamp(scalar: [u8; 32]) -> [u8; 32] {
+    let mut k = scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    k
+}
+
+
+///X25519 public key: the 32-byte u coordinate of k*9
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X25519PublicKey {
+    bytes: [u8; 32]
+}
+
+impl X25519PublicKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.bytes
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+}
+
+///X25519 key pair
+///
+/// fields:
+///     - secret = 32-byte scalar, clamped inside x25519()
+///     - public_key = x25519(secret, 9)
+///
+///the secret is overwritten with zeros on drop
+#[derive(Clone)]
+pub struct X25519KeyPair {
+    secret: [u8; 32],
+    public_key: X25519PublicKey
+}
+
+//only the public key is printed so the scalar can't leak through logs
+impl fmt::Debug for X25519KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("X25519KeyPair")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for X25519KeyPair {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl X25519KeyPair {
+    ///generate() draws 32 random bytes for the secret scalar
+    pub fn generate<R: CryptoRng + ?Sized>(rng: &mut R) -> Self {
+        let mut secret = [0u8; 32];
+        rng.fill_bytes(&mut secret);
+
+        Self::from_secret_bytes(secret)
+    }
+
+    pub fn from_secret_bytes(secret: [u8; 32]) -> Self {
+        let public_key = X25519PublicKey { bytes: x25519(secret, BASEPOINT) };
+
+        Self { secret, public_key }
+    }
+
+    pub fn public_key(&self) -> &X25519PublicKey {
+        &self.public_key
+    }
+
+    ///diffie_hellman() returns x25519(secret, peer public key)
+    ///
+    ///a peer key of small order gives the all-zero output, which is rejected as
+    ///RFC 7748 section 6.1 allows, so that neither party can force a known secret
+    pub fn diffie_hellman(&self, peer: &X25519PublicKey) -> Result<[u8; 32], X25519Error> {
+        let shared = x25519(self.secret, peer.bytes);
+
+        if bool::from(shared.ct_eq(&[0u8; 32])) {
+            return Err(X25519Error::LowOrderPoint)
+        }
+
+        Ok(shared)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn from_hex(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_rfc7748_vectors() {
+        //RFC 7748 section 5.2, the second u is non-canonical (top bit set)
+        let vectors = [
+            (
+                "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+                "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+                "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"
+            ),
+            (
+                "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+                "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+                "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957"
+            ),
+        ];
+
+        for (scalar, u, expected) in vectors {
+            assert_eq!(x25519(from_hex(scalar), from_hex(u)), from_hex(expected));
+        }
+    }
+
+    //RFC 7748 section 5.2: k = u = 9, then repeatedly k, u = x25519(k, u), k
+    fn iterate(iterations: usize) -> [u8; 32] {
+        let mut k = BASEPOINT;
+        let mut u = BASEPOINT;
+
+        for _ in 0..iterations {
+            let result = x25519(k, u);
+            u = k;
+            k = result;
+        }
+
+        k
+    }
+
+    #[test]
+    fn test_rfc7748_iterated() {
+        assert_eq!(iterate(1), from_hex("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079"));
+        assert_eq!(iterate(1000), from_hex("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51"));
+    }
+
+    //takes minutes, run with cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn test_rfc7748_iterated_million() {
+        assert_eq!(iterate(1_000_000), from_hex("7c3911e0ab2586fd864497297e575e6f3bc601c0883c30df5f4dd2d24f665424"));
+    }
+
+    #[test]
+    fn test_rfc7748_diffie_hellman() {
+        //RFC 7748 section 6.1
+        let alice = X25519KeyPair::from_secret_bytes(from_hex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"));
+        let bob = X25519KeyPair::from_secret_bytes(from_hex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb"));
+
+        assert_eq!(alice.public_key().to_bytes(), from_hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+        assert_eq!(bob.public_key().to_bytes(), from_hex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"));
+
+        let shared = from_hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(alice.diffie_hellman(bob.public_key()), Ok(shared));
+        assert_eq!(bob.diffie_hellman(alice.public_key()), Ok(shared));
+    }
+
+    #[test]
+    fn test_random_key_pairs() {
+        let mut rng = rand::rng();
+        let alice = X25519KeyPair::generate(&mut rng);
+        let bob = X25519KeyPair::generate(&mut rng);
+
+        assert_eq!(alice.diffie_hellman(bob.public_key()), bob.diffie_hellman(alice.public_key()));
+        assert_ne!(alice.public_key(), bob.public_key());
+    }
+
+    #[test]
+    fn test_low_order_point_rejected() {
+        let mut rng = rand::rng();
+        let key_pair = X25519KeyPair::generate(&mut rng);
+
+        //u = 0 is the point (0, 0) of order 2, u = 1 is a point of order 4, and u = p
+        //is a non-canonical encoding of 0
+        let p_bytes = P.to_le_bytes();
+        let mut one = [0u8; 32];
+        one[0] = 1;
+
+        for u in [[0u8; 32], one, p_bytes] {
+            let peer = X25519PublicKey::from_bytes(u);
+            assert_eq!(key_pair.diffie_hellman(&peer), Err(X25519Error::LowOrderPoint));
+        }
+    }
+}